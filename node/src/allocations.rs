@@ -0,0 +1,330 @@
+//! Genesis authorities, council and balance allocations read from a JSON or CSV file.
+//!
+//! Every allocation gives an account, the amount it is endowed with, the block its vesting
+//! starts at and the amount released per block after that. A `per_block` of zero means the
+//! whole amount is free at genesis. Every authority gives its account and the SS58 addresses of
+//! its Aura (or BABE) and GRANDPA session keys.
+//!
+//! JSON files hold an object:
+//!
+//! ```json
+//! {
+//!   "authorities": [{ "account": "5Grw...", "consensus": "5Grw...", "grandpa": "5FA9..." }],
+//!   "council": ["5Grw..."],
+//!   "allocations": [{ "account": "5Grw...", "amount": 1000000000000, "vesting_start": 0, "per_block": 1000000 }]
+//! }
+//! ```
+//!
+//! CSV files hold one `account,amount,vesting_start,per_block` line per allocation, one
+//! `authority,account,consensus,grandpa` line per authority and one `council,account` line per
+//! council member. A header line, blank lines and lines starting with `#` are skipped.
+
+use std::{collections::BTreeSet, convert::TryFrom, fs, path::Path};
+use serde::Deserialize;
+use sp_core::{crypto::Ss58Codec, ed25519, sr25519};
+use node_template_runtime::{AccountId, Balance, BlockNumber, ExistentialDeposit, opaque::SessionKeys};
+
+/// One account's share of the genesis supply.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Allocation {
+	/// SS58 address of the account.
+	pub account: String,
+	/// Amount the account is endowed with.
+	pub amount: Balance,
+	/// Block the vesting schedule starts at.
+	pub vesting_start: BlockNumber,
+	/// Amount released per block once vesting started.
+	pub per_block: Balance,
+}
+
+/// A genesis validator and its session keys.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Authority {
+	/// SS58 address of the validator account.
+	pub account: String,
+	/// SS58 address of the Aura (or BABE) key.
+	pub consensus: String,
+	/// SS58 address of the GRANDPA key.
+	pub grandpa: String,
+}
+
+/// Contents of an allocation file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AllocationFile {
+	/// Genesis validators.
+	pub authorities: Vec<Authority>,
+	/// SS58 addresses of the genesis council members.
+	pub council: Vec<String>,
+	/// Genesis balances and vesting schedules.
+	pub allocations: Vec<Allocation>,
+}
+
+/// Authorities, council, balances and vesting schedules ready for the genesis config.
+pub struct GenesisAllocations {
+	/// Validator accounts with their session keys.
+	pub authorities: Vec<(AccountId, SessionKeys)>,
+	/// Council members.
+	pub council: Vec<AccountId>,
+	/// `(account, amount)` for `pallet_balances`.
+	pub balances: Vec<(AccountId, Balance)>,
+	/// `(account, start, length, liquid)` for `pallet_vesting`.
+	pub vesting: Vec<(AccountId, BlockNumber, BlockNumber, Balance)>,
+}
+
+/// Read the allocation file at `path`, choosing the format by file extension.
+pub fn read(path: &Path) -> Result<AllocationFile, String> {
+	let content = fs::read_to_string(path)
+		.map_err(|e| format!("Error reading allocation file {}: {}", path.display(), e))?;
+
+	match path.extension().and_then(|ext| ext.to_str()) {
+		Some("json") => serde_json::from_str(&content)
+			.map_err(|e| format!("Error parsing allocation file {}: {}", path.display(), e)),
+		Some("csv") => parse_csv(&content),
+		_ => Err(format!("Allocation file {} must be .json or .csv", path.display())),
+	}
+}
+
+fn parse_csv(content: &str) -> Result<AllocationFile, String> {
+	let mut file = AllocationFile::default();
+
+	for (number, line) in content.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') || line.starts_with("account") {
+			continue;
+		}
+
+		let error = |what: &str| format!("Allocation line {}: {}", number + 1, what);
+		let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+		match fields[0] {
+			"authority" if fields.len() == 4 => {
+				file.authorities.push(Authority {
+					account: fields[1].to_string(),
+					consensus: fields[2].to_string(),
+					grandpa: fields[3].to_string(),
+				});
+				continue;
+			}
+			"authority" => return Err(error("expected authority,account,consensus,grandpa")),
+			"council" if fields.len() == 2 => {
+				file.council.push(fields[1].to_string());
+				continue;
+			}
+			"council" => return Err(error("expected council,account")),
+			_ if fields.len() != 4 => return Err(error("expected account,amount,vesting_start,per_block")),
+			_ => {}
+		}
+
+		file.allocations.push(Allocation {
+			account: fields[0].to_string(),
+			amount: fields[1].parse().map_err(|_| error("invalid amount"))?,
+			vesting_start: fields[2].parse().map_err(|_| error("invalid vesting start"))?,
+			per_block: fields[3].parse().map_err(|_| error("invalid per-block release"))?,
+		});
+	}
+
+	Ok(file)
+}
+
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(address).map_err(|e| format!("Invalid account {}: {:?}", address, e))
+}
+
+#[cfg(not(feature = "consensus-babe"))]
+fn session_keys(consensus: sr25519::Public, grandpa: ed25519::Public) -> SessionKeys {
+	SessionKeys { aura: consensus.into(), grandpa: grandpa.into() }
+}
+
+#[cfg(feature = "consensus-babe")]
+fn session_keys(consensus: sr25519::Public, grandpa: ed25519::Public) -> SessionKeys {
+	SessionKeys { babe: consensus.into(), grandpa: grandpa.into() }
+}
+
+fn parse_authority(authority: &Authority) -> Result<(AccountId, SessionKeys), String> {
+	let consensus = sr25519::Public::from_ss58check(&authority.consensus)
+		.map_err(|e| format!("Invalid consensus key {}: {:?}", authority.consensus, e))?;
+	let grandpa = ed25519::Public::from_ss58check(&authority.grandpa)
+		.map_err(|e| format!("Invalid GRANDPA key {}: {:?}", authority.grandpa, e))?;
+
+	Ok((parse_account(&authority.account)?, session_keys(consensus, grandpa)))
+}
+
+/// Check `file` names at least one authority and council member and its allocations add up to
+/// `total_supply`, and turn it into the genesis authorities, council, balances and vesting
+/// schedules.
+///
+/// `pallet_vesting` releases `amount / length` per block, so the amount of a vesting
+/// allocation must be a multiple of its `per_block` for the schedule to match the file.
+pub fn to_genesis(file: AllocationFile, total_supply: Balance) -> Result<GenesisAllocations, String> {
+	if file.authorities.is_empty() {
+		return Err("Allocation file names no authorities".to_string());
+	}
+	if file.council.is_empty() {
+		return Err("Allocation file names no council members".to_string());
+	}
+
+	let authorities = file.authorities.iter()
+		.map(parse_authority)
+		.collect::<Result<Vec<_>, _>>()?;
+	let mut validators = BTreeSet::new();
+	for (account, _) in &authorities {
+		if !validators.insert(account.clone()) {
+			return Err(format!("Authority {} is listed twice", account.to_ss58check()));
+		}
+	}
+
+	let mut council = Vec::with_capacity(file.council.len());
+	for address in &file.council {
+		let member = parse_account(address)?;
+		if council.contains(&member) {
+			return Err(format!("Council member {} is listed twice", address));
+		}
+		council.push(member);
+	}
+
+	let mut seen = BTreeSet::new();
+	let mut total: Balance = 0;
+	let mut balances = Vec::with_capacity(file.allocations.len());
+	let mut vesting = Vec::new();
+
+	for allocation in file.allocations {
+		let account = parse_account(&allocation.account)?;
+
+		if !seen.insert(account.clone()) {
+			return Err(format!("Account {} is allocated twice", allocation.account));
+		}
+		if allocation.amount < ExistentialDeposit::get() {
+			return Err(format!("Allocation of {} is below the existential deposit", allocation.account));
+		}
+
+		total = total.checked_add(allocation.amount)
+			.ok_or_else(|| "Allocations overflow the balance type".to_string())?;
+
+		if allocation.per_block > 0 && allocation.per_block < allocation.amount {
+			if allocation.amount % allocation.per_block != 0 {
+				return Err(format!("Allocation of {} is not a multiple of its per-block release", allocation.account));
+			}
+			let length = BlockNumber::try_from(allocation.amount / allocation.per_block)
+				.map_err(|_| format!("Vesting of {} takes too many blocks", allocation.account))?;
+			vesting.push((account.clone(), allocation.vesting_start, length, 0));
+		}
+
+		balances.push((account, allocation.amount));
+	}
+
+	if total != total_supply {
+		return Err(format!("Allocations add up to {}, expected a total supply of {}", total, total_supply));
+	}
+
+	Ok(GenesisAllocations { authorities, council, balances, vesting })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn address(seed: u8) -> String {
+		AccountId::from([seed; 32]).to_ss58check()
+	}
+
+	fn authority(seed: u8) -> Authority {
+		Authority {
+			account: address(seed),
+			consensus: sr25519::Public::from_raw([seed; 32]).to_ss58check(),
+			grandpa: ed25519::Public::from_raw([seed; 32]).to_ss58check(),
+		}
+	}
+
+	fn allocation(seed: u8, amount: Balance, per_block: Balance) -> Allocation {
+		Allocation { account: address(seed), amount, vesting_start: 10, per_block }
+	}
+
+	fn file(allocations: Vec<Allocation>) -> AllocationFile {
+		AllocationFile { authorities: vec![authority(1)], council: vec![address(1)], allocations }
+	}
+
+	#[test]
+	fn parse_csv_works() {
+		let csv = format!(
+			"account,amount,vesting_start,per_block\n# comment\n\n{},100,10,5\nauthority,{},{},{}\ncouncil, {}\n",
+			address(1), address(2), authority(2).consensus, authority(2).grandpa, address(3),
+		);
+
+		assert_eq!(parse_csv(&csv), Ok(AllocationFile {
+			authorities: vec![authority(2)],
+			council: vec![address(3)],
+			allocations: vec![allocation(1, 100, 5)],
+		}));
+	}
+
+	#[test]
+	fn parse_csv_rejects_malformed_lines() {
+		assert_eq!(parse_csv("a,1,2"), Err("Allocation line 1: expected account,amount,vesting_start,per_block".to_string()));
+		assert_eq!(parse_csv("\na,x,2,3"), Err("Allocation line 2: invalid amount".to_string()));
+		assert_eq!(parse_csv("authority,a,b"), Err("Allocation line 1: expected authority,account,consensus,grandpa".to_string()));
+		assert_eq!(parse_csv("council,a,b"), Err("Allocation line 1: expected council,account".to_string()));
+	}
+
+	#[test]
+	fn to_genesis_works() {
+		let ed = ExistentialDeposit::get();
+		let genesis = to_genesis(file(vec![allocation(1, 10 * ed, ed), allocation(2, ed, 0)]), 11 * ed).unwrap();
+
+		assert_eq!(genesis.authorities.len(), 1);
+		assert_eq!(genesis.authorities[0].0, AccountId::from([1; 32]));
+		assert_eq!(genesis.council, vec![AccountId::from([1; 32])]);
+		assert_eq!(genesis.balances, vec![(AccountId::from([1; 32]), 10 * ed), (AccountId::from([2; 32]), ed)]);
+		assert_eq!(genesis.vesting, vec![(AccountId::from([1; 32]), 10, 10, 0)]);
+	}
+
+	#[test]
+	fn to_genesis_checks_total_supply() {
+		let ed = ExistentialDeposit::get();
+
+		assert!(to_genesis(file(vec![allocation(1, ed, 0)]), 2 * ed).is_err());
+		assert!(to_genesis(file(vec![allocation(1, ed, 0), allocation(2, 2 * ed, 0)]), 2 * ed).is_err());
+		assert!(to_genesis(file(vec![allocation(1, Balance::max_value(), 0), allocation(2, ed, 0)]), ed).is_err());
+	}
+
+	#[test]
+	fn to_genesis_rejects_duplicates() {
+		let ed = ExistentialDeposit::get();
+
+		assert!(to_genesis(file(vec![allocation(1, ed, 0), allocation(1, ed, 0)]), 2 * ed).is_err());
+
+		let mut twice = file(vec![allocation(1, ed, 0)]);
+		twice.authorities.push(authority(1));
+		assert!(to_genesis(twice, ed).is_err());
+
+		let mut twice = file(vec![allocation(1, ed, 0)]);
+		twice.council.push(address(1));
+		assert!(to_genesis(twice, ed).is_err());
+	}
+
+	#[test]
+	fn to_genesis_rejects_allocation_below_existential_deposit() {
+		let ed = ExistentialDeposit::get();
+
+		assert!(to_genesis(file(vec![allocation(1, ed - 1, 0)]), ed - 1).is_err());
+	}
+
+	#[test]
+	fn to_genesis_rejects_uneven_vesting() {
+		let ed = ExistentialDeposit::get();
+
+		assert!(to_genesis(file(vec![allocation(1, 10 * ed, 3 * ed)]), 10 * ed).is_err());
+	}
+
+	#[test]
+	fn to_genesis_requires_authorities_and_council() {
+		let ed = ExistentialDeposit::get();
+
+		let mut no_authorities = file(vec![allocation(1, ed, 0)]);
+		no_authorities.authorities.clear();
+		assert!(to_genesis(no_authorities, ed).is_err());
+
+		let mut no_council = file(vec![allocation(1, ed, 0)]);
+		no_council.council.clear();
+		assert!(to_genesis(no_council, ed).is_err());
+	}
+}
@@ -1,18 +1,76 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	AccountId, BalancesConfig, GenesisConfig, GrandpaConfig,
+	SystemConfig, PoeModuleConfig, KittiesModuleConfig, CouncilConfig, DemocracyConfig,
+	IndicesConfig, VestingConfig, BlockTimeConfig, SessionConfig, KittyOracleConfig, WASM_BINARY,
+	Signature, Balance, BlockNumber, DOLLARS, SLOT_DURATION, opaque::SessionKeys,
 };
+#[cfg(not(feature = "no-sudo"))]
+use node_template_runtime::SudoConfig;
+#[cfg(not(feature = "consensus-babe"))]
+use node_template_runtime::AuraConfig;
+#[cfg(feature = "consensus-babe")]
+use node_template_runtime::BabeConfig;
+#[cfg(not(feature = "npos"))]
+use node_template_runtime::ValidatorSetConfig;
+#[cfg(feature = "npos")]
+use node_template_runtime::{StakingConfig, StakerStatus};
+#[cfg(not(feature = "consensus-babe"))]
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+#[cfg(feature = "consensus-babe")]
+use sp_consensus_babe::AuthorityId as BabeId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{Verify, IdentifyAccount};
+#[cfg(feature = "npos")]
+use sp_runtime::Perbill;
 use sc_service::ChainType;
+use sc_chain_spec::ChainSpecExtension;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::allocations;
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
+/// Total supply the allocation file of a public testnet must add up to.
+const TESTNET_TOTAL_SUPPLY: Balance = 100_000_000 * DOLLARS;
+
+/// Balance each initial validator bonds with `npos`.
+#[cfg(feature = "npos")]
+const VALIDATOR_STASH: Balance = 10_000 * DOLLARS;
+
+/// Node settings carried in the chain spec, next to the genesis config.
+///
+/// Specs written before these existed get the defaults.
+#[derive(Clone, Serialize, Deserialize, ChainSpecExtension)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Extensions {
+	/// How often GRANDPA gossips votes, in milliseconds.
+	pub grandpa_gossip_duration: u64,
+	/// Number of blocks between GRANDPA justifications.
+	pub grandpa_justification_period: u32,
+}
+
+impl Default for Extensions {
+	fn default() -> Self {
+		Extensions {
+			grandpa_gossip_duration: 333,
+			grandpa_justification_period: 512,
+		}
+	}
+}
+
+impl Extensions {
+	/// The extensions of `chain_spec`, or the defaults if it isn't one of ours.
+	pub fn from_chain_spec(chain_spec: &dyn sc_service::ChainSpec) -> Self {
+		sc_chain_spec::get_extension::<Extensions>(chain_spec.extensions())
+			.cloned()
+			.unwrap_or_default()
+	}
+}
+
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -30,14 +88,34 @@ pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
-/// Generate an Aura authority key.
-pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
+/// Generate a validator account with its session keys.
+///
+/// With `npos` the account is both stash and controller of the validator.
+pub fn authority_keys_from_seed(s: &str) -> (AccountId, SessionKeys) {
 	(
-		get_from_seed::<AuraId>(s),
-		get_from_seed::<GrandpaId>(s),
+		get_account_id_from_seed::<sr25519::Public>(s),
+		session_keys_from_seed(s),
 	)
 }
 
+/// Generate Aura and GRANDPA session keys.
+#[cfg(not(feature = "consensus-babe"))]
+fn session_keys_from_seed(s: &str) -> SessionKeys {
+	SessionKeys {
+		aura: get_from_seed::<AuraId>(s),
+		grandpa: get_from_seed::<GrandpaId>(s),
+	}
+}
+
+/// Generate BABE and GRANDPA session keys.
+#[cfg(feature = "consensus-babe")]
+fn session_keys_from_seed(s: &str) -> SessionKeys {
+	SessionKeys {
+		babe: get_from_seed::<BabeId>(s),
+		grandpa: get_from_seed::<GrandpaId>(s),
+	}
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
 
@@ -54,14 +132,20 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				authority_keys_from_seed("Alice"),
 			],
 			// Sudo account
-			get_account_id_from_seed::<sr25519::Public>("Alice"),
-			// Pre-funded accounts
+			Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+			// Council members
 			vec![
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
+			],
+			// Pre-funded accounts
+			endowed(vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				get_account_id_from_seed::<sr25519::Public>("Bob"),
 				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-			],
+			]),
+			// No vesting
+			vec![],
 			true,
 		),
 		// Bootnodes
@@ -73,7 +157,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
-		None,
+		Extensions::default(),
 	))
 }
 
@@ -94,12 +178,18 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				authority_keys_from_seed("Bob"),
 			],
 			// Sudo account
-			get_account_id_from_seed::<sr25519::Public>("Alice"),
-			// Pre-funded accounts
+			Some(get_account_id_from_seed::<sr25519::Public>("Alice")),
+			// Council members
 			vec![
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				get_account_id_from_seed::<sr25519::Public>("Bob"),
 				get_account_id_from_seed::<sr25519::Public>("Charlie"),
+			],
+			// Pre-funded accounts
+			endowed(vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				get_account_id_from_seed::<sr25519::Public>("Bob"),
+				get_account_id_from_seed::<sr25519::Public>("Charlie"),
 				get_account_id_from_seed::<sr25519::Public>("Dave"),
 				get_account_id_from_seed::<sr25519::Public>("Eve"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie"),
@@ -109,7 +199,9 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 				get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
-			],
+			]),
+			// No vesting
+			vec![],
 			true,
 		),
 		// Bootnodes
@@ -121,18 +213,67 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
+		Extensions::default(),
+	))
+}
+
+/// Public testnet whose authorities, council, balances and vesting schedules come from the
+/// allocation file at `path`, see `allocations` for its format. Fails if the file names no
+/// authorities or council members, there are no dev keys to fall back to on a live chain.
+pub fn public_testnet_config(path: &Path) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
+	let allocations = allocations::to_genesis(allocations::read(path)?, TESTNET_TOTAL_SUPPLY)?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Public Testnet",
+		// ID
+		"public_testnet",
+		ChainType::Live,
+		move || testnet_genesis(
+			wasm_binary,
+			allocations.authorities.clone(),
+			// No sudo, the council and referenda govern from genesis
+			None,
+			allocations.council.clone(),
+			allocations.balances.clone(),
+			allocations.vesting.clone(),
+			false,
+		),
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		// Properties
 		None,
+		// Extensions
+		Extensions::default(),
 	))
 }
 
+/// Endow each of `accounts` with 1 << 60.
+fn endowed(accounts: Vec<AccountId>) -> Vec<(AccountId, Balance)> {
+	accounts.into_iter().map(|k| (k, 1 << 60)).collect()
+}
+
 /// Configure initial storage state for FRAME modules.
+///
+/// Without a `root_key` the chain starts with no usable sudo key and is governed by
+/// `council_members` and referenda only. Runtimes built with `no-sudo` ignore `root_key`.
 fn testnet_genesis(
 	wasm_binary: &[u8],
-	initial_authorities: Vec<(AuraId, GrandpaId)>,
-	root_key: AccountId,
-	endowed_accounts: Vec<AccountId>,
+	initial_authorities: Vec<(AccountId, SessionKeys)>,
+	root_key: Option<AccountId>,
+	council_members: Vec<AccountId>,
+	endowed_accounts: Vec<(AccountId, Balance)>,
+	vesting: Vec<(AccountId, BlockNumber, BlockNumber, Balance)>,
 	_enable_println: bool,
 ) -> GenesisConfig {
+	#[cfg(feature = "no-sudo")]
+	let _ = root_key;
+
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
 			// Add Wasm runtime to storage.
@@ -140,18 +281,66 @@ fn testnet_genesis(
 			changes_trie_config: Default::default(),
 		}),
 		pallet_balances: Some(BalancesConfig {
-			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k|(k, 1 << 60)).collect(),
+			balances: endowed_accounts,
+		}),
+		pallet_vesting: Some(VestingConfig {
+			vesting,
+		}),
+		pallet_block_time: Some(BlockTimeConfig {
+			slot_duration: SLOT_DURATION,
+		}),
+		#[cfg(not(feature = "npos"))]
+		pallet_validator_set: Some(ValidatorSetConfig {
+			validators: initial_authorities.iter().map(|x| x.0.clone()).collect(),
 		}),
+		#[cfg(feature = "npos")]
+		pallet_staking: Some(StakingConfig {
+			validator_count: initial_authorities.len() as u32 * 2,
+			minimum_validator_count: initial_authorities.len() as u32,
+			stakers: initial_authorities.iter().map(|x| {
+				(x.0.clone(), x.0.clone(), VALIDATOR_STASH, StakerStatus::Validator)
+			}).collect(),
+			invulnerables: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+			slash_reward_fraction: Perbill::from_percent(10),
+			.. Default::default()
+		}),
+		pallet_session: Some(SessionConfig {
+			keys: initial_authorities.iter().map(|x| (x.0.clone(), x.0.clone(), x.1.clone())).collect(),
+		}),
+		// Aura (or BABE) and GRANDPA get their authorities from the session keys
+		#[cfg(not(feature = "consensus-babe"))]
 		pallet_aura: Some(AuraConfig {
-			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
+			authorities: vec![],
+		}),
+		#[cfg(feature = "consensus-babe")]
+		pallet_babe: Some(BabeConfig {
+			authorities: vec![],
 		}),
 		pallet_grandpa: Some(GrandpaConfig {
-			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
+			authorities: vec![],
 		}),
-		pallet_sudo: Some(SudoConfig {
+		#[cfg(not(feature = "no-sudo"))]
+		pallet_sudo: root_key.map(|key| SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key,
+		}),
+		pallet_collective_Instance1: Some(CouncilConfig {
+			members: council_members,
+			phantom: Default::default(),
+		}),
+		pallet_democracy: Some(DemocracyConfig::default()),
+		pallet_treasury: Some(Default::default()),
+		pallet_indices: Some(IndicesConfig {
+			indices: vec![],
+		}),
+		// Validators attest the availability of claimed content from genesis
+		pallet_poe: Some(PoeModuleConfig {
+			attesters: initial_authorities.iter().map(|x| x.0.clone()).collect(),
+		}),
+		pallet_kitties: Some(KittiesModuleConfig::default()),
+		// Validators report kitty floor prices from genesis
+		pallet_kitty_oracle: Some(KittyOracleConfig {
+			oracles: initial_authorities.iter().map(|x| x.0.clone()).collect(),
 		}),
 	}
 }
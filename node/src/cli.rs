@@ -1,5 +1,7 @@
+use std::path::PathBuf;
 use structopt::StructOpt;
 use sc_cli::RunCmd;
+use crate::service::Sealing;
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -8,6 +10,15 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Index kitty and PoE events and serve them over the `history_` RPC methods.
+	#[structopt(long)]
+	pub enable_history: bool,
+
+	/// Seal blocks without Aura and GRANDPA, for development and tests:
+	/// `instant`, `manual` or `interval=<ms>`. Only on development and local chains.
+	#[structopt(long)]
+	pub sealing: Option<Sealing>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -36,4 +47,16 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Dry-run the runtime's storage migrations against exported state.
+	#[structopt(name = "try-runtime", about = "Run runtime storage migrations against a state snapshot.")]
+	TryRuntime(TryRuntimeCmd),
+}
+
+/// The `try-runtime` command.
+#[derive(Debug, StructOpt)]
+pub struct TryRuntimeCmd {
+	/// Chain spec holding the state to migrate, as written by `export-state`.
+	#[structopt(long, parse(from_os_str))]
+	pub snapshot: PathBuf,
 }
@@ -18,7 +18,7 @@
 use crate::{chain_spec, service};
 use crate::cli::{Cli, Subcommand};
 use sc_cli::{SubstrateCli, RuntimeVersion, Role, ChainSpec};
-use sc_service::PartialComponents;
+use sc_service::{ChainType, PartialComponents};
 use node_template_runtime::Block;
 
 impl SubstrateCli for Cli {
@@ -50,6 +50,9 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
+			id if id.starts_with("testnet:") => Box::new(chain_spec::public_testnet_config(
+				std::path::Path::new(&id["testnet:".len()..]),
+			)?),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
@@ -124,11 +127,20 @@ pub fn run() -> sc_cli::Result<()> {
 				You can enable it with `--features runtime-benchmarks`.".into())
 			}
 		},
+		Some(Subcommand::TryRuntime(cmd)) => crate::try_runtime::run(cmd),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let enable_history = cli.enable_history;
+			let sealing = cli.sealing;
 			runner.run_node_until_exit(|config| match config.role {
 				Role::Light => service::new_light(config),
-				_ => service::new_full(config),
+				_ => match sealing {
+					// sealed blocks skip consensus, keep them off shared chains
+					Some(_) if !matches!(config.chain_spec.chain_type(), ChainType::Development | ChainType::Local) =>
+						Err("--sealing only works on development and local chains".into()),
+					Some(sealing) => service::new_manual_seal(config, sealing, enable_history),
+					None => service::new_full(config, enable_history),
+				},
 			})
 		}
 	}
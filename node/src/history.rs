@@ -0,0 +1,569 @@
+//! Off-chain index of kitty and PoE events, served over the `history_` RPC namespace.
+//!
+//! Events only live in `System::Events` for the block that emitted them. With
+//! `--enable-history` the node reads them from every block that becomes part of the best
+//! chain, keeps the kitty and PoE ones in a sled database next to the chain database, and
+//! answers queries like "all transfers of kitty 42" from there.
+//!
+//! Blocks leaving the best chain in a reorg are dropped from the index again. Catching up
+//! with blocks imported before the index was enabled needs their state, so run the node with
+//! `--pruning archive` to index a chain from genesis.
+//!
+//! Blocks whose events can't be read, because their state is pruned or they were emitted by a
+//! runtime with a different event layout, are recorded as gaps and served by `history_gaps`,
+//! so clients know which parts of a history may be missing.
+
+use std::{path::Path, sync::Arc};
+use codec::Decode;
+use futures::StreamExt;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::{blake2_256, twox_128}, storage::StorageKey, Bytes};
+use sp_runtime::{generic::BlockId, traits::Header as _};
+use node_template_runtime::{
+	opaque::Block, AccountId, Balance, BlockNumber, Event, Hash, KittyIndex,
+};
+
+pub(crate) type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// Events deposited by the block `hash`.
+pub(crate) fn block_events<B, C>(client: &C, hash: Hash) -> Result<Vec<EventRecord>, String> where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	let key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+
+	match client.storage(&BlockId::Hash(hash), &key) {
+		Ok(Some(data)) => Vec::<EventRecord>::decode(&mut &data.0[..])
+			// events of an older runtime with a different layout
+			.map_err(|e| format!("Can't decode events: {:?}", e)),
+		Ok(None) => Ok(Vec::new()),
+		Err(e) => Err(format!("Events unavailable, is the state pruned? {}", e)),
+	}
+}
+
+/// A kitty or PoE event, as stored in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryEvent {
+	/// A kitty is created, bred or minted.
+	#[serde(rename_all = "camelCase")]
+	KittyCreated { owner: AccountId, kitty_id: KittyIndex },
+	/// A kitty is transferred, also as part of a swap.
+	#[serde(rename_all = "camelCase")]
+	KittyTransferred { from: AccountId, to: AccountId, kitty_id: KittyIndex },
+	/// A kitty is put up for sale, or taken off sale with no price.
+	#[serde(rename_all = "camelCase")]
+	KittyAsk { owner: AccountId, kitty_id: KittyIndex, price: Option<Balance> },
+	/// A kitty is sold.
+	#[serde(rename_all = "camelCase")]
+	KittySold { from: AccountId, to: AccountId, kitty_id: KittyIndex, price: Balance },
+	/// A kitty is burned.
+	#[serde(rename_all = "camelCase")]
+	KittyBurned { owner: AccountId, kitty_id: KittyIndex },
+	/// A claim is created.
+	ClaimCreated { owner: AccountId, claim: Bytes },
+	/// A claim is transferred.
+	ClaimTransferred { from: AccountId, to: AccountId, claim: Bytes },
+	/// A claim is revoked by its owner or by force.
+	ClaimRevoked { owner: AccountId, claim: Bytes },
+	/// A claim expiry is set or cleared.
+	#[serde(rename_all = "camelCase")]
+	ClaimExpiryChanged { owner: AccountId, claim: Bytes, expires_at: Option<BlockNumber> },
+	/// A claim is revoked because it expired.
+	ClaimExpired { owner: AccountId, claim: Bytes },
+}
+
+impl HistoryEvent {
+	/// Pick the events the index keeps out of all runtime events.
+	fn from_runtime(event: Event) -> Option<Self> {
+		use pallet_kitties::RawEvent as Kitties;
+		use pallet_poe::RawEvent as Poe;
+
+		Some(match event {
+			Event::pallet_kitties(Kitties::Created(owner, kitty_id)) =>
+				HistoryEvent::KittyCreated { owner, kitty_id },
+			Event::pallet_kitties(Kitties::Transferred(from, to, kitty_id)) =>
+				HistoryEvent::KittyTransferred { from, to, kitty_id },
+			Event::pallet_kitties(Kitties::Ask(owner, kitty_id, price)) =>
+				HistoryEvent::KittyAsk { owner, kitty_id, price },
+			Event::pallet_kitties(Kitties::Sold(from, to, kitty_id, price)) =>
+				HistoryEvent::KittySold { from, to, kitty_id, price },
+			Event::pallet_kitties(Kitties::Burned(owner, kitty_id)) =>
+				HistoryEvent::KittyBurned { owner, kitty_id },
+			Event::pallet_poe(Poe::ClaimCreated(owner, claim)) =>
+				HistoryEvent::ClaimCreated { owner, claim: claim.into() },
+			Event::pallet_poe(Poe::ClaimTransferred(from, to, claim)) =>
+				HistoryEvent::ClaimTransferred { from, to, claim: claim.into() },
+			Event::pallet_poe(Poe::ClaimRevoked(owner, claim)) =>
+				HistoryEvent::ClaimRevoked { owner, claim: claim.into() },
+			Event::pallet_poe(Poe::ClaimExpiryChanged(owner, claim, expires_at)) =>
+				HistoryEvent::ClaimExpiryChanged { owner, claim: claim.into(), expires_at },
+			Event::pallet_poe(Poe::ClaimExpired(owner, claim)) =>
+				HistoryEvent::ClaimExpired { owner, claim: claim.into() },
+			_ => return None,
+		})
+	}
+
+	fn kitty_id(&self) -> Option<KittyIndex> {
+		match self {
+			HistoryEvent::KittyCreated { kitty_id, .. }
+			| HistoryEvent::KittyTransferred { kitty_id, .. }
+			| HistoryEvent::KittyAsk { kitty_id, .. }
+			| HistoryEvent::KittySold { kitty_id, .. }
+			| HistoryEvent::KittyBurned { kitty_id, .. } => Some(*kitty_id),
+			_ => None,
+		}
+	}
+
+	fn claim(&self) -> Option<&Bytes> {
+		match self {
+			HistoryEvent::ClaimCreated { claim, .. }
+			| HistoryEvent::ClaimTransferred { claim, .. }
+			| HistoryEvent::ClaimRevoked { claim, .. }
+			| HistoryEvent::ClaimExpiryChanged { claim, .. }
+			| HistoryEvent::ClaimExpired { claim, .. } => Some(claim),
+			_ => None,
+		}
+	}
+
+	fn accounts(&self) -> Vec<&AccountId> {
+		match self {
+			HistoryEvent::KittyTransferred { from, to, .. }
+			| HistoryEvent::KittySold { from, to, .. }
+			| HistoryEvent::ClaimTransferred { from, to, .. } => vec![from, to],
+			HistoryEvent::KittyCreated { owner, .. }
+			| HistoryEvent::KittyAsk { owner, .. }
+			| HistoryEvent::KittyBurned { owner, .. }
+			| HistoryEvent::ClaimCreated { owner, .. }
+			| HistoryEvent::ClaimRevoked { owner, .. }
+			| HistoryEvent::ClaimExpiryChanged { owner, .. }
+			| HistoryEvent::ClaimExpired { owner, .. } => vec![owner],
+		}
+	}
+}
+
+/// An indexed event and where it was emitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+	/// Number of the block that emitted the event.
+	pub block_number: BlockNumber,
+	/// Hash of the block that emitted the event.
+	pub block_hash: Hash,
+	/// Position of the event among the block's events.
+	pub event_index: u32,
+	/// The event itself.
+	pub event: HistoryEvent,
+}
+
+/// Keys written for a block, so its entries can be dropped when it is retracted.
+#[derive(Default, Serialize, Deserialize)]
+struct BlockKeys {
+	kitties: Vec<Vec<u8>>,
+	claims: Vec<Vec<u8>>,
+	accounts: Vec<Vec<u8>>,
+}
+
+/// A best-chain block whose events are missing from the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryGap {
+	/// Number of the block.
+	pub block_number: BlockNumber,
+	/// Hash of the block.
+	pub block_hash: Hash,
+	/// Why its events couldn't be read.
+	pub reason: String,
+}
+
+const LAST_INDEXED: &[u8] = b"last_indexed";
+
+/// The history database.
+///
+/// Every entry is stored under `prefix ++ block_number ++ event_index`, big endian, so a prefix
+/// scan returns an item's history in chain order. Indexing the same block twice writes the same
+/// keys again. Gaps are stored under `block_number ++ block_hash`.
+pub struct HistoryDb {
+	db: sled::Db,
+	kitties: sled::Tree,
+	claims: sled::Tree,
+	accounts: sled::Tree,
+	blocks: sled::Tree,
+	gaps: sled::Tree,
+}
+
+fn db_error(e: sled::Error) -> String {
+	format!("History database error: {}", e)
+}
+
+fn entry_key(prefix: &[u8], entry: &HistoryEntry) -> Vec<u8> {
+	let mut key = prefix.to_vec();
+	key.extend_from_slice(&entry.block_number.to_be_bytes());
+	key.extend_from_slice(&entry.event_index.to_be_bytes());
+	key
+}
+
+fn gap_key(number: BlockNumber, hash: &Hash) -> Vec<u8> {
+	[&number.to_be_bytes()[..], hash.as_bytes()].concat()
+}
+
+impl HistoryDb {
+	/// Open the database at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, String> {
+		Self::from_db(sled::open(path).map_err(db_error)?)
+	}
+
+	fn from_db(db: sled::Db) -> Result<Self, String> {
+		Ok(HistoryDb {
+			kitties: db.open_tree("kitties").map_err(db_error)?,
+			claims: db.open_tree("claims").map_err(db_error)?,
+			accounts: db.open_tree("accounts").map_err(db_error)?,
+			blocks: db.open_tree("blocks").map_err(db_error)?,
+			gaps: db.open_tree("gaps").map_err(db_error)?,
+			db,
+		})
+	}
+
+	/// History of a kitty.
+	pub fn kitty(&self, kitty_id: KittyIndex) -> Result<Vec<HistoryEntry>, String> {
+		Self::scan(&self.kitties, &kitty_id.to_be_bytes())
+	}
+
+	/// History of a claim.
+	pub fn claim(&self, claim: &[u8]) -> Result<Vec<HistoryEntry>, String> {
+		Self::scan(&self.claims, &blake2_256(claim))
+	}
+
+	/// Kitty and PoE events involving an account.
+	pub fn account(&self, account: &AccountId) -> Result<Vec<HistoryEntry>, String> {
+		Self::scan(&self.accounts, account.as_ref())
+	}
+
+	/// Best-chain blocks whose events are missing, oldest first.
+	pub fn gaps(&self) -> Result<Vec<HistoryGap>, String> {
+		self.gaps.iter()
+			.map(|item| {
+				let (_, value) = item.map_err(db_error)?;
+				serde_json::from_slice(&value).map_err(|e| format!("Corrupt history gap: {}", e))
+			})
+			.collect()
+	}
+
+	fn scan(tree: &sled::Tree, prefix: &[u8]) -> Result<Vec<HistoryEntry>, String> {
+		tree.scan_prefix(prefix)
+			.map(|item| {
+				let (_, value) = item.map_err(db_error)?;
+				serde_json::from_slice(&value).map_err(|e| format!("Corrupt history entry: {}", e))
+			})
+			.collect()
+	}
+
+	fn last_indexed(&self) -> Result<Option<(BlockNumber, Hash)>, String> {
+		match self.db.get(LAST_INDEXED).map_err(db_error)? {
+			Some(value) => serde_json::from_slice(&value)
+				.map(Some)
+				.map_err(|e| format!("Corrupt history metadata: {}", e)),
+			None => Ok(None),
+		}
+	}
+
+	fn set_last_indexed(&self, last: Option<(BlockNumber, Hash)>) -> Result<(), String> {
+		match last {
+			Some(last) => {
+				let value = serde_json::to_vec(&last).expect("serializing a tuple can't fail; qed");
+				self.db.insert(LAST_INDEXED, value).map_err(db_error)?;
+			},
+			None => {
+				self.db.remove(LAST_INDEXED).map_err(db_error)?;
+			},
+		}
+		Ok(())
+	}
+
+	/// Bring the index in line with the best chain up to `best`.
+	///
+	/// Indexed blocks that are no longer on the best chain are dropped first, then every block
+	/// after the last indexed one is indexed.
+	fn sync<B, C>(&self, client: &C, best: BlockNumber) -> Result<(), String> where
+		B: Backend<Block>,
+		C: HeaderBackend<Block> + StorageProvider<Block, B>,
+	{
+		let mut last = self.last_indexed()?;
+
+		while let Some((number, hash)) = last {
+			if client.hash(number).map_err(|e| e.to_string())? == Some(hash) {
+				break;
+			}
+
+			self.remove_block(number, &hash)?;
+			last = match number.checked_sub(1) {
+				Some(parent_number) => {
+					let header = client.header(BlockId::Hash(hash)).map_err(|e| e.to_string())?
+						.ok_or_else(|| format!("Header of retracted block {} is gone", hash))?;
+					Some((parent_number, *header.parent_hash()))
+				},
+				None => None,
+			};
+			self.set_last_indexed(last)?;
+		}
+
+		let from = last.map_or(0, |(number, _)| number + 1);
+		for number in from..=best {
+			let hash = match client.hash(number).map_err(|e| e.to_string())? {
+				Some(hash) => hash,
+				None => break,
+			};
+
+			self.index_block(client, number, hash)?;
+			self.set_last_indexed(Some((number, hash)))?;
+		}
+
+		Ok(())
+	}
+
+	fn index_block<B, C>(&self, client: &C, number: BlockNumber, hash: Hash) -> Result<(), String> where
+		B: Backend<Block>,
+		C: StorageProvider<Block, B>,
+	{
+		match block_events(client, hash) {
+			Ok(records) => self.write_block(number, hash, records),
+			Err(reason) => {
+				log::warn!(target: "history", "Skipping block #{}: {}", number, reason);
+				self.write_gap(number, hash, reason)
+			},
+		}
+	}
+
+	fn write_gap(&self, number: BlockNumber, hash: Hash, reason: String) -> Result<(), String> {
+		let gap = HistoryGap { block_number: number, block_hash: hash, reason };
+		let value = serde_json::to_vec(&gap).map_err(|e| e.to_string())?;
+		self.gaps.insert(gap_key(number, &hash), value).map_err(db_error)?;
+
+		// so the gap is dropped with the block in a reorg
+		let keys = serde_json::to_vec(&BlockKeys::default()).map_err(|e| e.to_string())?;
+		self.blocks.insert(hash.as_bytes(), keys).map_err(db_error)?;
+
+		Ok(())
+	}
+
+	fn write_block(&self, number: BlockNumber, hash: Hash, records: Vec<EventRecord>) -> Result<(), String> {
+		let mut keys = BlockKeys::default();
+
+		for (event_index, record) in records.into_iter().enumerate() {
+			let event = match HistoryEvent::from_runtime(record.event) {
+				Some(event) => event,
+				None => continue,
+			};
+			let entry = HistoryEntry { block_number: number, block_hash: hash, event_index: event_index as u32, event };
+			let value = serde_json::to_vec(&entry).map_err(|e| e.to_string())?;
+
+			if let Some(kitty_id) = entry.event.kitty_id() {
+				let key = entry_key(&kitty_id.to_be_bytes(), &entry);
+				self.kitties.insert(&key, value.clone()).map_err(db_error)?;
+				keys.kitties.push(key);
+			}
+			if let Some(claim) = entry.event.claim() {
+				let key = entry_key(&blake2_256(claim), &entry);
+				self.claims.insert(&key, value.clone()).map_err(db_error)?;
+				keys.claims.push(key);
+			}
+			for account in entry.event.accounts() {
+				let key = entry_key(account.as_ref(), &entry);
+				self.accounts.insert(&key, value.clone()).map_err(db_error)?;
+				keys.accounts.push(key);
+			}
+		}
+
+		let keys = serde_json::to_vec(&keys).map_err(|e| e.to_string())?;
+		self.blocks.insert(hash.as_bytes(), keys).map_err(db_error)?;
+
+		Ok(())
+	}
+
+	fn remove_block(&self, number: BlockNumber, hash: &Hash) -> Result<(), String> {
+		self.gaps.remove(gap_key(number, hash)).map_err(db_error)?;
+
+		let keys: BlockKeys = match self.blocks.get(hash.as_bytes()).map_err(db_error)? {
+			Some(value) => serde_json::from_slice(&value).map_err(|e| format!("Corrupt history block: {}", e))?,
+			None => return Ok(()),
+		};
+
+		for key in keys.kitties {
+			self.kitties.remove(key).map_err(db_error)?;
+		}
+		for key in keys.claims {
+			self.claims.remove(key).map_err(db_error)?;
+		}
+		for key in keys.accounts {
+			self.accounts.remove(key).map_err(db_error)?;
+		}
+		self.blocks.remove(hash.as_bytes()).map_err(db_error)?;
+
+		Ok(())
+	}
+}
+
+/// Keep `db` in line with the best chain of `client`, for as long as the node runs.
+pub async fn run_indexer<B, C>(client: Arc<C>, db: Arc<HistoryDb>) where
+	B: Backend<Block>,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+{
+	// subscribe first so nothing imported while catching up is missed
+	let mut imports = client.import_notification_stream();
+
+	if let Err(e) = db.sync(&*client, client.info().best_number) {
+		log::error!(target: "history", "Indexing failed: {}", e);
+	}
+
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+		if let Err(e) = db.sync(&*client, *notification.header.number()) {
+			log::error!(target: "history", "Indexing failed: {}", e);
+		}
+	}
+}
+
+/// History RPC methods.
+#[rpc(server)]
+pub trait HistoryApi {
+	/// Events of a kitty, oldest first.
+	#[rpc(name = "history_kitty")]
+	fn kitty(&self, kitty_id: KittyIndex) -> RpcResult<Vec<HistoryEntry>>;
+
+	/// Events of a claim, oldest first.
+	#[rpc(name = "history_claim")]
+	fn claim(&self, claim: Bytes) -> RpcResult<Vec<HistoryEntry>>;
+
+	/// Kitty and PoE events involving an account, oldest first.
+	#[rpc(name = "history_account")]
+	fn account(&self, account: AccountId) -> RpcResult<Vec<HistoryEntry>>;
+
+	/// Best-chain blocks whose events couldn't be indexed, oldest first.
+	#[rpc(name = "history_gaps")]
+	fn gaps(&self) -> RpcResult<Vec<HistoryGap>>;
+}
+
+/// Serves `HistoryApi` from a `HistoryDb`.
+pub struct History {
+	db: Arc<HistoryDb>,
+}
+
+impl History {
+	/// Create a new `History` reading from `db`.
+	pub fn new(db: Arc<HistoryDb>) -> Self {
+		History { db }
+	}
+}
+
+fn rpc_error(message: String) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message,
+		data: None,
+	}
+}
+
+impl HistoryApi for History {
+	fn kitty(&self, kitty_id: KittyIndex) -> RpcResult<Vec<HistoryEntry>> {
+		self.db.kitty(kitty_id).map_err(rpc_error)
+	}
+
+	fn claim(&self, claim: Bytes) -> RpcResult<Vec<HistoryEntry>> {
+		self.db.claim(&claim).map_err(rpc_error)
+	}
+
+	fn account(&self, account: AccountId) -> RpcResult<Vec<HistoryEntry>> {
+		self.db.account(&account).map_err(rpc_error)
+	}
+
+	fn gaps(&self) -> RpcResult<Vec<HistoryGap>> {
+		self.db.gaps().map_err(rpc_error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::Phase;
+
+	fn temporary_db() -> HistoryDb {
+		HistoryDb::from_db(sled::Config::new().temporary(true).open().unwrap()).unwrap()
+	}
+
+	fn record(event: Event) -> EventRecord {
+		EventRecord { phase: Phase::ApplyExtrinsic(0), event, topics: vec![] }
+	}
+
+	fn created(owner: u8, kitty_id: KittyIndex) -> EventRecord {
+		record(Event::pallet_kitties(pallet_kitties::RawEvent::Created(AccountId::from([owner; 32]), kitty_id)))
+	}
+
+	fn transferred(from: u8, to: u8, kitty_id: KittyIndex) -> EventRecord {
+		record(Event::pallet_kitties(pallet_kitties::RawEvent::Transferred(
+			AccountId::from([from; 32]),
+			AccountId::from([to; 32]),
+			kitty_id,
+		)))
+	}
+
+	#[test]
+	fn entries_are_keyed_by_item_block_and_event_index() {
+		let db = temporary_db();
+		db.write_block(256, Hash::repeat_byte(2), vec![transferred(1, 2, 7)]).unwrap();
+		db.write_block(2, Hash::repeat_byte(1), vec![created(3, 8), created(1, 7)]).unwrap();
+
+		let keys: Vec<Vec<u8>> = db.kitties.iter().keys().map(|key| key.unwrap().to_vec()).collect();
+		let key = |kitty_id: KittyIndex, number: BlockNumber, event_index: u32| {
+			[&kitty_id.to_be_bytes()[..], &number.to_be_bytes()[..], &event_index.to_be_bytes()[..]].concat()
+		};
+		assert_eq!(keys, vec![key(7, 2, 1), key(7, 256, 0), key(8, 2, 0)]);
+
+		// chain order, not insertion order
+		let history = db.kitty(7).unwrap();
+		assert_eq!(history.iter().map(|entry| entry.block_number).collect::<Vec<_>>(), vec![2, 256]);
+		assert_eq!(history[0].event, HistoryEvent::KittyCreated { owner: AccountId::from([1; 32]), kitty_id: 7 });
+
+		assert_eq!(db.account(&AccountId::from([1; 32])).unwrap().len(), 2);
+		assert_eq!(db.account(&AccountId::from([2; 32])).unwrap().len(), 1);
+	}
+
+	#[test]
+	fn removed_block_leaves_no_entries() {
+		let db = temporary_db();
+		db.write_block(1, Hash::repeat_byte(1), vec![created(1, 7)]).unwrap();
+		db.write_block(2, Hash::repeat_byte(2), vec![transferred(1, 2, 7)]).unwrap();
+		db.write_gap(3, Hash::repeat_byte(3), "pruned".into()).unwrap();
+
+		db.remove_block(3, &Hash::repeat_byte(3)).unwrap();
+		db.remove_block(2, &Hash::repeat_byte(2)).unwrap();
+
+		assert_eq!(db.kitty(7).unwrap().len(), 1);
+		assert!(db.account(&AccountId::from([2; 32])).unwrap().is_empty());
+		assert!(db.gaps().unwrap().is_empty());
+		assert_eq!(db.blocks.len(), 1);
+
+		// indexing the new best block after the reorg
+		db.write_block(2, Hash::repeat_byte(4), vec![transferred(1, 3, 7)]).unwrap();
+		let history = db.kitty(7).unwrap();
+		assert_eq!(history.len(), 2);
+		assert_eq!(history[1].block_hash, Hash::repeat_byte(4));
+	}
+
+	#[test]
+	fn gaps_are_recorded_in_chain_order() {
+		let db = temporary_db();
+		db.write_gap(300, Hash::repeat_byte(2), "pruned".into()).unwrap();
+		db.write_gap(4, Hash::repeat_byte(1), "undecodable".into()).unwrap();
+
+		assert_eq!(db.gaps().unwrap(), vec![
+			HistoryGap { block_number: 4, block_hash: Hash::repeat_byte(1), reason: "undecodable".into() },
+			HistoryGap { block_number: 300, block_hash: Hash::repeat_byte(2), reason: "pruned".into() },
+		]);
+	}
+}
@@ -1,3 +1,7 @@
+mod allocations;
 pub mod chain_spec;
+pub mod history;
+pub mod metrics;
+pub mod proofs;
 pub mod service;
 pub mod rpc;
@@ -1,12 +1,17 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+mod allocations;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
+mod history;
+mod metrics;
+mod proofs;
 mod rpc;
+mod try_runtime;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
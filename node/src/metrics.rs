@@ -0,0 +1,133 @@
+//! Prometheus metrics of kitty and PoE activity.
+//!
+//! Collected from the events of every block that becomes the new best block, and exposed on
+//! the node's Prometheus endpoint next to Substrate's own metrics. Blocks imported during the
+//! initial sync aren't announced, so the counters cover activity since the node caught up.
+
+use std::sync::Arc;
+use futures::StreamExt;
+use prometheus_endpoint::{
+	exponential_buckets, register, Counter, CounterVec, Gauge, Histogram, HistogramOpts, Opts,
+	PrometheusError, Registry, F64, U64,
+};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_core::{hashing::{blake2_128, twox_128}, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use codec::Encode;
+use node_template_runtime::{opaque::Block, Event, Hash, KittyIndex};
+
+/// Kitty and PoE metrics.
+pub struct Metrics {
+	kitty_events: CounterVec<U64>,
+	market_volume: Counter<F64>,
+	active_asks: Gauge<U64>,
+	claim_events: CounterVec<U64>,
+	claim_length: Histogram,
+}
+
+impl Metrics {
+	/// Register the metrics on `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Metrics {
+			kitty_events: register(CounterVec::new(
+				Opts::new("kitties_events_total", "Kitties created, bred, transferred, sold and burned"),
+				&["event"],
+			)?, registry)?,
+			market_volume: register(Counter::new(
+				"kitties_market_volume_total", "Sum of the prices of sold kitties, in the smallest unit",
+			)?, registry)?,
+			active_asks: register(Gauge::new(
+				"kitties_active_asks", "Kitties for sale at the best block",
+			)?, registry)?,
+			claim_events: register(CounterVec::new(
+				Opts::new("poe_claims_total", "PoE claims created, transferred, revoked and expired"),
+				&["event"],
+			)?, registry)?,
+			claim_length: register(Histogram::with_opts(
+				HistogramOpts::new("poe_claim_length_bytes", "Length of created PoE claims")
+					.buckets(exponential_buckets(1.0, 2.0, 9)?),
+			)?, registry)?,
+		})
+	}
+
+	/// Count the events of the block `hash`.
+	fn observe_block<B, C>(&self, client: &C, hash: Hash) -> Result<(), String> where
+		B: Backend<Block>,
+		C: StorageProvider<Block, B>,
+	{
+		use pallet_kitties::RawEvent as Kitties;
+		use pallet_poe::RawEvent as Poe;
+
+		let mut asks_changed = false;
+
+		for record in crate::history::block_events(client, hash)? {
+			match record.event {
+				Event::pallet_kitties(Kitties::Created(_, kitty_id)) => {
+					let parents = kitties_key(b"KittyParents", kitty_id);
+					let bred = client.storage(&BlockId::Hash(hash), &parents)
+						.map_err(|e| e.to_string())?
+						.is_some();
+					self.kitty_events.with_label_values(&[if bred { "bred" } else { "created" }]).inc();
+				},
+				Event::pallet_kitties(Kitties::Transferred(..)) => {
+					self.kitty_events.with_label_values(&["transferred"]).inc();
+					asks_changed = true;
+				},
+				Event::pallet_kitties(Kitties::Sold(_, _, _, price)) => {
+					self.kitty_events.with_label_values(&["sold"]).inc();
+					self.market_volume.inc_by(price as f64);
+					asks_changed = true;
+				},
+				Event::pallet_kitties(Kitties::Burned(..)) => {
+					self.kitty_events.with_label_values(&["burned"]).inc();
+					asks_changed = true;
+				},
+				Event::pallet_kitties(Kitties::Ask(..)) => asks_changed = true,
+				Event::pallet_poe(Poe::ClaimCreated(_, claim)) => {
+					self.claim_events.with_label_values(&["created"]).inc();
+					self.claim_length.observe(claim.len() as f64);
+				},
+				Event::pallet_poe(Poe::ClaimTransferred(..)) =>
+					self.claim_events.with_label_values(&["transferred"]).inc(),
+				Event::pallet_poe(Poe::ClaimRevoked(..)) =>
+					self.claim_events.with_label_values(&["revoked"]).inc(),
+				Event::pallet_poe(Poe::ClaimExpired(..)) =>
+					self.claim_events.with_label_values(&["expired"]).inc(),
+				_ => {},
+			}
+		}
+
+		// counting the asks reads the whole price map, so only after it may have changed
+		if asks_changed || self.active_asks.get() == 0 {
+			let prefix = StorageKey([twox_128(b"Kitties"), twox_128(b"KittyPrices")].concat());
+			let asks = client.storage_keys(&BlockId::Hash(hash), &prefix).map_err(|e| e.to_string())?;
+			self.active_asks.set(asks.len() as u64);
+		}
+
+		Ok(())
+	}
+}
+
+/// Storage key of a kitty's entry in the `blake2_128_concat` map `item` of the kitties pallet.
+fn kitties_key(item: &[u8], kitty_id: KittyIndex) -> StorageKey {
+	let kitty_id = kitty_id.encode();
+
+	StorageKey([&twox_128(b"Kitties")[..], &twox_128(item)[..], &blake2_128(&kitty_id)[..], &kitty_id[..]].concat())
+}
+
+/// Update `metrics` with every new best block.
+pub async fn run_metrics<B, C>(client: Arc<C>, metrics: Metrics) where
+	B: Backend<Block>,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B>,
+{
+	let mut imports = client.import_notification_stream();
+
+	while let Some(notification) = imports.next().await {
+		if !notification.is_new_best {
+			continue;
+		}
+		if let Err(e) = metrics.observe_block(&*client, notification.hash) {
+			log::warn!(target: "metrics", "Kitty and PoE metrics of block {} skipped: {}", notification.hash, e);
+		}
+	}
+}
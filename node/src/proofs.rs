@@ -0,0 +1,70 @@
+//! Storage read proofs of kitty owners and PoE claims, served over the `proof_` RPC namespace.
+//!
+//! `state_getReadProof` can prove any key, but callers have to know how the pallets lay out
+//! their storage. These methods compute the keys, and `node-template-proof-verifier` checks the
+//! proofs against a state root without trusting this node.
+
+use std::sync::Arc;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
+use sc_client_api::ProofProvider;
+use sc_rpc_api::state::ReadProof;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::generic::BlockId;
+use node_template_proof_verifier::{claim_key, kitty_owner_key};
+use node_template_runtime::{opaque::Block, Hash, KittyIndex};
+
+/// Storage proof RPC methods.
+#[rpc(server)]
+pub trait ProofApi {
+	/// Proof of the owner of a kitty, or of there being none, at block `at`, by default the
+	/// last finalized one.
+	#[rpc(name = "proof_kittyOwner")]
+	fn kitty_owner(&self, kitty_id: KittyIndex, at: Option<Hash>) -> RpcResult<ReadProof<Hash>>;
+
+	/// Proof of a claim, or of there being none, at block `at`, by default the last finalized
+	/// one.
+	#[rpc(name = "proof_claim")]
+	fn claim(&self, claim: Bytes, at: Option<Hash>) -> RpcResult<ReadProof<Hash>>;
+}
+
+/// Serves `ProofApi` from the client's state.
+pub struct Proofs<C> {
+	client: Arc<C>,
+}
+
+impl<C> Proofs<C> {
+	/// Create a new `Proofs` reading from `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Proofs { client }
+	}
+}
+
+impl<C> Proofs<C> where
+	C: ProofProvider<Block> + HeaderBackend<Block>,
+{
+	fn read_proof(&self, key: &[u8], at: Option<Hash>) -> RpcResult<ReadProof<Hash>> {
+		let at = at.unwrap_or_else(|| self.client.info().finalized_hash);
+
+		self.client.read_proof(&BlockId::Hash(at), &mut std::iter::once(key))
+			.map(|proof| ReadProof { at, proof: proof.iter_nodes().map(Into::into).collect() })
+			.map_err(|e| RpcError {
+				code: ErrorCode::ServerError(1),
+				message: format!("Reading proof failed: {}", e),
+				data: None,
+			})
+	}
+}
+
+impl<C> ProofApi for Proofs<C> where
+	C: ProofProvider<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+{
+	fn kitty_owner(&self, kitty_id: KittyIndex, at: Option<Hash>) -> RpcResult<ReadProof<Hash>> {
+		self.read_proof(&kitty_owner_key(kitty_id), at)
+	}
+
+	fn claim(&self, claim: Bytes, at: Option<Hash>) -> RpcResult<ReadProof<Hash>> {
+		self.read_proof(&claim_key(&claim), at)
+	}
+}
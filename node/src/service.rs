@@ -1,13 +1,23 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
+use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
-use sc_client_api::{ExecutorProvider, RemoteBackend};
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use codec::Decode;
+use futures::{channel::mpsc, stream::{self, BoxStream}, StreamExt};
+use sc_client_api::{ExecutorProvider, RemoteBackend, StorageProvider};
+use sc_consensus_manual_seal::{EngineCommand, ManualSealParams};
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
-use sp_inherents::InherentDataProviders;
+use sp_inherents::{InherentData, InherentDataProviders, InherentIdentifier, ProvideInherentData};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use sp_transaction_pool::TransactionPool;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
+#[cfg(not(feature = "consensus-babe"))]
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
 use sc_finality_grandpa::{FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState};
 
@@ -22,19 +32,33 @@ native_executor_instance!(
 type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullGrandpaBlockImport =
+	sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>;
+
+/// Block import of the slot consensus, Aura or with `consensus-babe` BABE, wrapping GRANDPA's.
+#[cfg(not(feature = "consensus-babe"))]
+type ConsensusBlockImport =
+	sc_consensus_aura::AuraBlockImport<Block, FullClient, FullGrandpaBlockImport, AuraPair>;
+/// Block import of the slot consensus, Aura or with `consensus-babe` BABE, wrapping GRANDPA's.
+#[cfg(feature = "consensus-babe")]
+type ConsensusBlockImport =
+	sc_consensus_babe::BabeBlockImport<Block, FullClient, FullGrandpaBlockImport>;
+
+/// What the slot consensus' authoring task shares with its import queue, nothing for Aura.
+#[cfg(not(feature = "consensus-babe"))]
+type ConsensusLink = ();
+/// What the slot consensus' authoring task shares with its import queue, nothing for Aura.
+#[cfg(feature = "consensus-babe")]
+type ConsensusLink = sc_consensus_babe::BabeLink<Block>;
 
 pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponents<
 	FullClient, FullBackend, FullSelectChain,
 	sp_consensus::DefaultImportQueue<Block, FullClient>,
 	sc_transaction_pool::FullPool<Block, FullClient>,
 	(
-		sc_consensus_aura::AuraBlockImport<
-			Block,
-			FullClient,
-			sc_finality_grandpa::GrandpaBlockImport<FullBackend, Block, FullClient, FullSelectChain>,
-			AuraPair
-		>,
-		sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>
+		ConsensusBlockImport,
+		sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+		ConsensusLink,
 	)
 >, ServiceError> {
 	let inherent_data_providers = sp_inherents::InherentDataProviders::new();
@@ -56,35 +80,113 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 		client.clone(), &(client.clone() as Arc<_>), select_chain.clone(),
 	)?;
 
-	let aura_block_import = sc_consensus_aura::AuraBlockImport::<_, _, _, AuraPair>::new(
-		grandpa_block_import.clone(), client.clone(),
-	);
+	#[cfg(not(feature = "consensus-babe"))]
+	let (block_import, consensus_link, import_queue) = {
+		let aura_block_import = sc_consensus_aura::AuraBlockImport::<_, _, _, AuraPair>::new(
+			grandpa_block_import.clone(), client.clone(),
+		);
 
-	let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
-		sc_consensus_aura::slot_duration(&*client)?,
-		aura_block_import.clone(),
-		Some(Box::new(grandpa_block_import.clone())),
-		None,
-		client.clone(),
-		inherent_data_providers.clone(),
-		&task_manager.spawn_handle(),
-		config.prometheus_registry(),
-		sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
-	)?;
+		let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
+			sc_consensus_aura::slot_duration(&*client)?,
+			aura_block_import.clone(),
+			Some(Box::new(grandpa_block_import.clone())),
+			None,
+			client.clone(),
+			inherent_data_providers.clone(),
+			&task_manager.spawn_handle(),
+			config.prometheus_registry(),
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+		)?;
+
+		(aura_block_import, (), import_queue)
+	};
+
+	#[cfg(feature = "consensus-babe")]
+	let (block_import, consensus_link, import_queue) = {
+		let (babe_block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client)?,
+			grandpa_block_import.clone(),
+			client.clone(),
+		)?;
+
+		let import_queue = sc_consensus_babe::import_queue(
+			babe_link.clone(),
+			babe_block_import.clone(),
+			Some(Box::new(grandpa_block_import.clone())),
+			None,
+			client.clone(),
+			select_chain.clone(),
+			inherent_data_providers.clone(),
+			&task_manager.spawn_handle(),
+			config.prometheus_registry(),
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone()),
+		)?;
+
+		(babe_block_import, babe_link, import_queue)
+	};
 
 	Ok(sc_service::PartialComponents {
 		client, backend, task_manager, import_queue, keystore, select_chain, transaction_pool,
 		inherent_data_providers,
-		other: (aura_block_import, grandpa_link),
+		other: (block_import, grandpa_link, consensus_link),
 	})
 }
 
+/// Open the history database and start indexing into it, if `enable_history`.
+fn spawn_history(
+	config: &Configuration,
+	client: &Arc<FullClient>,
+	task_manager: &TaskManager,
+	enable_history: bool,
+) -> Result<Option<Arc<crate::history::HistoryDb>>, ServiceError> {
+	if !enable_history {
+		return Ok(None);
+	}
+
+	let path = config.database.path()
+		.and_then(|path| path.parent())
+		.ok_or_else(|| ServiceError::Other("--enable-history needs an on-disk database".into()))?
+		.join("history");
+	let db = Arc::new(crate::history::HistoryDb::open(&path).map_err(ServiceError::Other)?);
+
+	task_manager.spawn_handle().spawn(
+		"history-indexer",
+		crate::history::run_indexer::<FullBackend, _>(client.clone(), db.clone()),
+	);
+
+	Ok(Some(db))
+}
+
+/// Register the kitty and PoE metrics and start collecting them, if Prometheus is enabled.
+fn spawn_metrics(
+	config: &Configuration,
+	client: &Arc<FullClient>,
+	task_manager: &TaskManager,
+) -> Result<(), ServiceError> {
+	let registry = match config.prometheus_registry() {
+		Some(registry) => registry,
+		None => return Ok(()),
+	};
+	let metrics = crate::metrics::Metrics::register(registry)
+		.map_err(|e| ServiceError::Other(format!("Registering metrics failed: {}", e)))?;
+
+	task_manager.spawn_handle().spawn(
+		"kitty-poe-metrics",
+		crate::metrics::run_metrics::<FullBackend, _>(client.clone(), metrics),
+	);
+
+	Ok(())
+}
+
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// With `enable_history` kitty and PoE events are indexed into `history` next to the chain
+/// database and served over RPC.
+pub fn new_full(config: Configuration, enable_history: bool) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client, backend, mut task_manager, import_queue, keystore, select_chain, transaction_pool,
 		inherent_data_providers,
-		other: (block_import, grandpa_link),
+		other: (block_import, grandpa_link, consensus_link),
 	} = new_partial(&config)?;
 
 	let finality_proof_provider =
@@ -110,12 +212,16 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	}
 
 	let role = config.role.clone();
+	let extensions = crate::chain_spec::Extensions::from_chain_spec(&*config.chain_spec);
 	let force_authoring = config.force_authoring;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
+	let history = spawn_history(&config, &client, &task_manager, enable_history)?;
+	spawn_metrics(&config, &client, &task_manager)?;
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
@@ -125,6 +231,8 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				history: history.clone(),
+				command_sink: None,
 			};
 
 			crate::rpc::create_full(deps)
@@ -154,22 +262,47 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		let can_author_with =
 			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
 
-		let aura = sc_consensus_aura::start_aura::<_, _, _, _, _, AuraPair, _, _, _>(
-			sc_consensus_aura::slot_duration(&*client)?,
-			client.clone(),
-			select_chain,
-			block_import,
-			proposer,
-			network.clone(),
-			inherent_data_providers.clone(),
-			force_authoring,
-			keystore.clone(),
-			can_author_with,
-		)?;
-
-		// the AURA authoring task is considered essential, i.e. if it
-		// fails we take down the service with it.
-		task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		#[cfg(not(feature = "consensus-babe"))]
+		{
+			// Aura's authoring shares nothing with its import queue
+			let () = consensus_link;
+			let aura = sc_consensus_aura::start_aura::<_, _, _, _, _, AuraPair, _, _, _>(
+				sc_consensus_aura::slot_duration(&*client)?,
+				client.clone(),
+				select_chain,
+				block_import,
+				proposer,
+				network.clone(),
+				inherent_data_providers.clone(),
+				force_authoring,
+				keystore.clone(),
+				can_author_with,
+			)?;
+
+			// the AURA authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("aura", aura);
+		}
+
+		#[cfg(feature = "consensus-babe")]
+		{
+			let babe = sc_consensus_babe::start_babe(sc_consensus_babe::BabeParams {
+				keystore: keystore.clone(),
+				client: client.clone(),
+				select_chain,
+				env: proposer,
+				block_import,
+				sync_oracle: network.clone(),
+				inherent_data_providers: inherent_data_providers.clone(),
+				force_authoring,
+				babe_link: consensus_link,
+				can_author_with,
+			})?;
+
+			// the BABE authoring task is considered essential, i.e. if it
+			// fails we take down the service with it.
+			task_manager.spawn_essential_handle().spawn_blocking("babe-proposer", babe);
+		}
 	}
 
 	// if the node isn't actively participating in consensus then it doesn't
@@ -181,9 +314,8 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	};
 
 	let grandpa_config = sc_finality_grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: Duration::from_millis(extensions.grandpa_gossip_duration),
+		justification_period: extensions.grandpa_justification_period,
 		name: Some(name),
 		observer_enabled: false,
 		keystore,
@@ -226,6 +358,222 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	Ok(task_manager)
 }
 
+/// How blocks are sealed when the node runs without Aura (or BABE) and GRANDPA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool.
+	Instant,
+	/// Seal blocks only when `engine_createBlock` is called.
+	Manual,
+	/// Seal a block every given number of milliseconds.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			s if s.starts_with("interval=") => match s["interval=".len()..].parse() {
+				Ok(0) | Err(_) => Err(format!("Invalid sealing interval {}, expected milliseconds above 0", s)),
+				Ok(millis) => Ok(Sealing::Interval(millis)),
+			},
+			s => Err(format!("Unknown sealing {}, expected instant, manual or interval=<ms>", s)),
+		}
+	}
+}
+
+/// Timestamps for sealed blocks, one slot apart.
+///
+/// The runtime still runs Aura, which only accepts one block per slot, so block timestamps
+/// can't follow the wall clock when blocks are sealed faster than that. Sealed blocks carry no
+/// BABE slot, so with `consensus-babe` epochs and sessions don't change while sealing.
+struct SlotTimestampProvider {
+	next: AtomicU64,
+	slot_duration: u64,
+}
+
+impl SlotTimestampProvider {
+	/// Start at the current slot, or one slot after `best_timestamp` if sealing ran ahead of
+	/// the wall clock before the node restarted.
+	fn new(slot_duration: u64, best_timestamp: u64) -> Self {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH)
+			.expect("the current time is after the unix epoch; qed")
+			.as_millis() as u64;
+		let next = (now - now % slot_duration).max(best_timestamp + slot_duration);
+
+		SlotTimestampProvider { next: AtomicU64::new(next), slot_duration }
+	}
+}
+
+/// `Timestamp::Now` of the best block, 0 at genesis.
+fn best_timestamp(client: &FullClient) -> Result<u64, ServiceError> {
+	let key = StorageKey([twox_128(b"Timestamp"), twox_128(b"Now")].concat());
+
+	match client.storage(&BlockId::Hash(client.info().best_hash), &key)? {
+		Some(data) => u64::decode(&mut &data.0[..])
+			.map_err(|e| ServiceError::Other(format!("Can't decode the best block's timestamp: {:?}", e))),
+		None => Ok(0),
+	}
+}
+
+impl ProvideInherentData for SlotTimestampProvider {
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&sp_timestamp::INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		let timestamp = self.next.fetch_add(self.slot_duration, Ordering::SeqCst);
+		inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		sp_timestamp::InherentError::try_from(&sp_timestamp::INHERENT_IDENTIFIER, error)
+			.map(|e| format!("{:?}", e))
+	}
+}
+
+/// Seal a block on top of the best block and finalize it right away.
+fn seal_command(create_empty: bool) -> EngineCommand<Hash> {
+	EngineCommand::SealNewBlock {
+		create_empty,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	}
+}
+
+/// Builds a full client for development that seals blocks as `sealing` says, instead of
+/// running Aura (or BABE) and GRANDPA. `engine_createBlock` and `engine_finalizeBlock` work with any
+/// `sealing`. Offchain workers run on every sealed block, as with `new_full`.
+pub fn new_manual_seal(
+	config: Configuration,
+	sealing: Sealing,
+	enable_history: bool,
+) -> Result<TaskManager, ServiceError> {
+	let (client, backend, keystore, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
+	let client = Arc::new(client);
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.prometheus_registry(),
+		task_manager.spawn_handle(),
+		client.clone(),
+	);
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, network_status_sinks, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+			finality_proof_request_builder: None,
+			finality_proof_provider: None,
+		})?;
+
+	// oracle reports and PoE attestations are submitted by offchain workers
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let history = spawn_history(&config, &client, &task_manager, enable_history)?;
+	spawn_metrics(&config, &client, &task_manager)?;
+	let (command_sink, rpc_commands) = mpsc::channel(1024);
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let pool = transaction_pool.clone();
+
+		Box::new(move |deny_unsafe, _| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				history: history.clone(),
+				command_sink: Some(command_sink.clone()),
+			};
+
+			crate::rpc::create_full(deps)
+		})
+	};
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore,
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
+		rpc_extensions_builder,
+		on_demand: None,
+		remote_blockchain: None,
+		backend, network_status_sinks, system_rpc_tx, config,
+	})?;
+
+	let commands_stream: BoxStream<'static, EngineCommand<Hash>> = match sealing {
+		Sealing::Manual => rpc_commands.boxed(),
+		Sealing::Instant => stream::select(
+			rpc_commands,
+			transaction_pool.import_notification_stream().map(|_| seal_command(false)),
+		).boxed(),
+		Sealing::Interval(millis) => stream::select(
+			rpc_commands,
+			stream::unfold((), move |_| async move {
+				futures_timer::Delay::new(Duration::from_millis(millis)).await;
+				Some((seal_command(true), ()))
+			}),
+		).boxed(),
+	};
+
+	let inherent_data_providers = InherentDataProviders::new();
+	#[cfg(not(feature = "consensus-babe"))]
+	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.get();
+	#[cfg(feature = "consensus-babe")]
+	let slot_duration = sc_consensus_babe::Config::get_or_compute(&*client)?.slot_duration;
+	inherent_data_providers.register_provider(SlotTimestampProvider::new(slot_duration, best_timestamp(&client)?))
+		.map_err(|e| ServiceError::Other(format!("{:?}", e)))?;
+
+	let proposer = sc_basic_authorship::ProposerFactory::new(
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+	);
+
+	let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer,
+		client,
+		pool: transaction_pool.pool().clone(),
+		commands_stream,
+		select_chain,
+		consensus_data_provider: None,
+		inherent_data_providers,
+	});
+
+	// the sealing task is the only block author, the node is useless without it
+	task_manager.spawn_essential_handle().spawn_blocking("manual-seal", authorship);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let (client, backend, keystore, mut task_manager, on_demand) =
@@ -247,6 +595,7 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let finality_proof_request_builder =
 		finality_proof_import.create_finality_proof_request_builder();
 
+	#[cfg(not(feature = "consensus-babe"))]
 	let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _, _>(
 		sc_consensus_aura::slot_duration(&*client)?,
 		grandpa_block_import,
@@ -259,6 +608,28 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 		sp_consensus::NeverCanAuthor,
 	)?;
 
+	#[cfg(feature = "consensus-babe")]
+	let import_queue = {
+		let (babe_block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client)?,
+			grandpa_block_import,
+			client.clone(),
+		)?;
+
+		sc_consensus_babe::import_queue(
+			babe_link,
+			babe_block_import,
+			None,
+			Some(Box::new(finality_proof_import)),
+			client.clone(),
+			sc_consensus::LongestChain::new(backend.clone()),
+			InherentDataProviders::new(),
+			&task_manager.spawn_handle(),
+			config.prometheus_registry(),
+			sp_consensus::NeverCanAuthor,
+		)?
+	};
+
 	let finality_proof_provider =
 		GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
 
@@ -301,3 +672,37 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 
 	 Ok(task_manager)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sealing_from_str_works() {
+		assert_eq!("instant".parse::<Sealing>(), Ok(Sealing::Instant));
+		assert_eq!("manual".parse::<Sealing>(), Ok(Sealing::Manual));
+		assert_eq!("interval=6000".parse::<Sealing>(), Ok(Sealing::Interval(6000)));
+	}
+
+	#[test]
+	fn sealing_from_str_rejects_invalid_values() {
+		assert!("interval=0".parse::<Sealing>().is_err());
+		assert!("interval=".parse::<Sealing>().is_err());
+		assert!("interval=-1".parse::<Sealing>().is_err());
+		assert!("interval=1s".parse::<Sealing>().is_err());
+		assert!("Instant".parse::<Sealing>().is_err());
+		assert!("".parse::<Sealing>().is_err());
+	}
+
+	#[test]
+	fn slot_timestamps_continue_after_best_block() {
+		let far_future = u64::max_value() / 2;
+		let provider = SlotTimestampProvider::new(1000, far_future);
+		assert_eq!(provider.next.load(Ordering::SeqCst), far_future + 1000);
+
+		let provider = SlotTimestampProvider::new(1000, 0);
+		let next = provider.next.load(Ordering::SeqCst);
+		assert!(next > 1000);
+		assert_eq!(next % 1000, 0);
+	}
+}
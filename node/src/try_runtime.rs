@@ -0,0 +1,24 @@
+//! Dry run of the runtime's storage migrations against a snapshot of exported state.
+//!
+//! The snapshot is loaded into an in-memory externalities and the migrations run natively,
+//! nothing is written back and no node is started.
+
+use crate::{chain_spec, cli::TryRuntimeCmd};
+use node_template_runtime::{BuildStorage, MaximumBlockWeight};
+
+/// Run the migrations on `cmd.snapshot` and report the weight they would use.
+pub fn run(cmd: &TryRuntimeCmd) -> sc_cli::Result<()> {
+	let spec = chain_spec::ChainSpec::from_json_file(cmd.snapshot.clone())?;
+	let storage = spec.build_storage()?;
+	let mut ext = sp_io::TestExternalities::new(storage);
+
+	let weight = ext.execute_with(node_template_runtime::try_runtime_upgrade)?;
+	let max_weight = MaximumBlockWeight::get();
+
+	println!("Migrations passed all checks, weight {} of a maximum block weight {}.", weight, max_weight);
+	if weight > max_weight {
+		return Err("Migrations don't fit in a block.".into());
+	}
+
+	Ok(())
+}
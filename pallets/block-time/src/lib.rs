@@ -0,0 +1,70 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Aura slot duration set in the genesis config rather than compiled into the runtime.
+//!
+//! Aura derives its slot duration from the timestamp pallet's `MinimumPeriod`, so a runtime
+//! uses `MinimumPeriod<Self>` there. BABE takes `ExpectedBlockTime<Self>` as well. Chains whose genesis predates this pallet, or that leave
+//! `slot_duration` at zero, keep `DefaultSlotDuration`.
+//!
+//! Only the slot duration changes. Periods the runtime counts in blocks, derived from its
+//! compiled-in block time, last `slot_duration / DefaultSlotDuration` times as long as intended.
+
+use frame_support::{decl_module, decl_storage, StorageValue, traits::Get};
+use sp_std::marker::PhantomData;
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub trait Trait: frame_system::Trait {
+    /// Slot duration in milliseconds when genesis doesn't set one.
+    type DefaultSlotDuration: Get<u64>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as BlockTime {
+        // milliseconds per slot, zero for `DefaultSlotDuration`
+        SlotDuration config(): u64;
+    }
+    add_extra_genesis {
+        build(|config: &GenesisConfig| {
+            assert!(config.slot_duration % 2 == 0, "Slot duration must be an even number of milliseconds");
+        })
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Milliseconds per Aura slot.
+    pub fn slot_duration() -> u64 {
+        match SlotDuration::get() {
+            0 => T::DefaultSlotDuration::get(),
+            slot_duration => slot_duration,
+        }
+    }
+}
+
+/// Half the slot duration. Use it as the timestamp pallet's `MinimumPeriod`.
+pub struct MinimumPeriod<T>(PhantomData<T>);
+
+impl<T: Trait> Get<u64> for MinimumPeriod<T> {
+    fn get() -> u64 {
+        Module::<T>::slot_duration() / 2
+    }
+}
+
+/// The slot duration. Use it as BABE's `ExpectedBlockTime`.
+pub struct ExpectedBlockTime<T>(PhantomData<T>);
+
+impl<T: Trait> Get<u64> for ExpectedBlockTime<T> {
+    fn get() -> u64 {
+        Module::<T>::slot_duration()
+    }
+}
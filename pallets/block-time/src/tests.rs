@@ -0,0 +1,26 @@
+use crate::{mock::*, MinimumPeriod, ExpectedBlockTime};
+use frame_support::traits::Get;
+
+#[test]
+fn genesis_slot_duration_is_used() {
+    new_test_ext(1000).execute_with(|| {
+        assert_eq!(BlockTime::slot_duration(), 1000);
+        assert_eq!(MinimumPeriod::<Test>::get(), 500);
+        assert_eq!(ExpectedBlockTime::<Test>::get(), 1000);
+    })
+}
+
+#[test]
+fn unset_slot_duration_falls_back_to_default() {
+    new_test_ext(0).execute_with(|| {
+        assert_eq!(BlockTime::slot_duration(), 6000);
+        assert_eq!(MinimumPeriod::<Test>::get(), 3000);
+        assert_eq!(ExpectedBlockTime::<Test>::get(), 6000);
+    })
+}
+
+#[test]
+#[should_panic(expected = "Slot duration must be an even number of milliseconds")]
+fn odd_slot_duration_is_rejected() {
+    new_test_ext(1001);
+}
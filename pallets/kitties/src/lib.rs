@@ -2,14 +2,20 @@
 
 use codec::{Encode, Decode};
 use frame_support::{decl_module, decl_storage, decl_event, dispatch,
-                    ensure, decl_error, StorageValue, StorageMap, Parameter,
-                    traits::{Get, Randomness, Currency, ExistenceRequirement},
+                    ensure, decl_error, StorageValue, StorageMap, StorageDoubleMap,
+                    Parameter, RuntimeDebug,
+                    traits::{
+                        Get, Randomness, Currency, ExistenceRequirement, BalanceStatus, EnsureOrigin,
+                        OnUnbalanced, WithdrawReason,
+                    },
+                    weights::Weight,
+                    transactional,
 };
 use sp_io::hashing::blake2_128;
 use frame_system::ensure_signed;
 use sp_runtime::{
-    traits::{AtLeast32BitUnsigned, Bounded},
-    DispatchError,
+    traits::{AtLeast32BitUnsigned, Bounded, One, Zero, Saturating, CheckedAdd, StaticLookup},
+    DispatchError, Permill,
 };
 use sp_std::prelude::*;
 use sp_std::ops::Index;
@@ -17,6 +23,11 @@ use sp_std::vec;
 use frame_support::traits::ReservableCurrency;
 
 
+pub mod traits;
+pub use traits::{NftInspect, NftTransfer, OnKittyTransfer, OnKittyBurn};
+
+pub mod migrations;
+
 // import test file
 #[cfg(test)]
 mod mock;
@@ -25,7 +36,7 @@ mod mock;
 mod tests;
 
 /// DNA
-#[derive(Encode, Decode, Clone, Copy)]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
 pub struct DNA(pub [u8; 16]);
 
 impl DNA {
@@ -79,7 +90,53 @@ impl Kitty {
     }
 }
 
+/// A pending offer to swap kitties between two accounts.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct SwapOffer<AccountId, KittyIndex, Balance> {
+    /// Account making the offer.
+    pub maker: AccountId,
+    /// Account the offer is addressed to.
+    pub taker: AccountId,
+    /// Kitties the maker gives away.
+    pub offered: vec::Vec<KittyIndex>,
+    /// Kitties the maker wants from the taker.
+    pub wanted: vec::Vec<KittyIndex>,
+    /// Balance the maker pays on top of the offered kitties.
+    pub balance: Option<Balance>,
+}
+
+pub type SwapIndex = u32;
+
+/// Storage layouts of this pallet, in order.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// Owned kitties kept as one `KittyTotal` vector per owner.
+    V1_0_0,
+    /// Owned kitties indexed by position, see `OwnedKittiesArray`.
+    V2_0_0,
+    /// Creation deposits recorded per kitty, see `KittyDeposits`.
+    V3_0_0,
+    /// `KittyChildren` keyed by both parents instead of the second parent twice.
+    V4_0_0,
+    /// Open swap offers counted per kitty, see `SwapOfferedKitties`.
+    V5_0_0,
+}
+
+impl Releases {
+    /// Layout this version of the pallet reads and writes.
+    pub const LATEST: Releases = Releases::V5_0_0;
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
 
 pub trait Trait: frame_system::Trait {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
@@ -87,24 +144,63 @@ pub trait Trait: frame_system::Trait {
     type KittyIndex: Parameter + Default + AtLeast32BitUnsigned + Copy + Bounded;
     type NewKittyReserve: Get<BalanceOf<Self>>;
     type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+    /// Maximum number of kitties moved by a single batch transfer or on each side of a swap.
+    type MaxBatchSize: Get<u32>;
+    /// Called whenever a kitty changes owner.
+    type OnKittyTransfer: OnKittyTransfer<Self::AccountId, Self::KittyIndex>;
+    /// Called whenever a kitty is burned.
+    type OnKittyBurn: OnKittyBurn<Self::AccountId, Self::KittyIndex>;
+    /// Whether ids of burned kitties are handed out again before fresh ones.
+    type ReuseKittyIds: Get<bool>;
+    /// Origin allowed to mint kitties with a chosen DNA.
+    type MintOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin allowed to move kitties without their owner's consent.
+    type ForceOrigin: EnsureOrigin<Self::Origin>;
+    /// Cut of every market sale, paid by the buyer out of the price.
+    type MarketCommission: Get<Permill>;
+    /// Receives the market commission, e.g. the treasury.
+    type OnCommission: OnUnbalanced<NegativeImbalanceOf<Self>>;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Kitties {
         // kitty id => kitty
         pub Kitties get(fn kitties): map hasher(blake2_128_concat) T::KittyIndex => Option<Kitty>;
-        // kitty count
+        // next fresh kitty id, burned kitties are not subtracted
         pub KittiesCount get(fn kitties_count): T::KittyIndex;
+        // number of ids on the free list
+        pub FreeKittyIdsCount get(fn free_kitty_ids_count): T::KittyIndex;
+        // free list position => id of a burned kitty
+        pub FreeKittyIds get(fn free_kitty_ids): map hasher(twox_64_concat) T::KittyIndex => Option<T::KittyIndex>;
         // kitty id => owner
         pub KittyOwners get(fn kitty_owner): map hasher(blake2_128_concat) T::KittyIndex => Option<T::AccountId>;
-        pub KittyTotal get(fn kitty_total): map hasher(blake2_128_concat) T::AccountId => vec::Vec<T::KittyIndex>;
+        // owner => number of kitties owned
+        pub OwnedKittiesCount get(fn owned_kitties_count): map hasher(blake2_128_concat) T::AccountId => T::KittyIndex;
+        // (owner, position) => kitty id
+        pub OwnedKittiesArray get(fn owned_kitties_array): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) T::KittyIndex => Option<T::KittyIndex>;
+        // kitty id => position in the owner's kitty list
+        pub OwnedKittiesIndex get(fn owned_kitties_index): map hasher(blake2_128_concat) T::KittyIndex => T::KittyIndex;
         pub KittyParents get(fn kitty_parents): map hasher(blake2_128_concat) T::KittyIndex => (T::KittyIndex, T::KittyIndex);
         pub KittyChildren get(fn kitty_children): double_map hasher(blake2_128_concat) T::KittyIndex, hasher(blake2_128_concat) T::KittyIndex => vec::Vec<T::KittyIndex>;
         pub KittyBrother get(fn kitty_brother): map hasher(blake2_128_concat) T::KittyIndex => vec::Vec<T::KittyIndex>;
         pub KittyPartner get(fn kitty_partner): map hasher(blake2_128_concat) T::KittyIndex => T::KittyIndex;
+        // kitty id => (account the deposit is reserved from, amount)
+        pub KittyDeposits get(fn kitty_deposit): map hasher(blake2_128_concat) T::KittyIndex => Option<(T::AccountId, BalanceOf<T>)>;
+        // kitty id => locked by another pallet
+        pub LockedKitties get(fn kitty_locked): map hasher(blake2_128_concat) T::KittyIndex => bool;
 
         // Get kitty price. None means not for sale.
 		pub KittyPrices get(fn kitty_price): map hasher(blake2_128_concat) T::KittyIndex => Option<BalanceOf<T>>;
+
+        // swap id => pending swap offer
+        pub SwapOffers get(fn swap_offer): map hasher(twox_64_concat) SwapIndex => Option<SwapOffer<T::AccountId, T::KittyIndex, BalanceOf<T>>>;
+        // swap offer count
+        pub SwapOffersCount get(fn swap_offers_count): SwapIndex;
+        // kitty id => number of open swap offers naming it
+        pub SwapOfferedKitties get(fn swap_offered_kitties): map hasher(blake2_128_concat) T::KittyIndex => u32;
+
+        // Storage layout version. Chains started before versioning read as V1_0_0.
+        pub StorageVersion get(fn storage_version) build(|_: &GenesisConfig| Releases::LATEST): Releases;
     }
 }
 
@@ -122,6 +218,14 @@ decl_event!(
 		Ask(AccountId, KittyIndex, Option<Balance>),
 		/// A kitty is sold. (from, to, kitty_id, price)
 		Sold(AccountId, AccountId, KittyIndex, Balance),
+		/// A kitty is burned and its deposit released. (owner, kitty_id)
+		Burned(AccountId, KittyIndex),
+		/// A swap is offered. (maker, taker, swap_id)
+		SwapOffered(AccountId, AccountId, SwapIndex),
+		/// A swap offer is cancelled by its maker. (maker, swap_id)
+		SwapCancelled(AccountId, SwapIndex),
+		/// A swap is accepted and all items have moved. (maker, taker, swap_id)
+		SwapAccepted(AccountId, AccountId, SwapIndex),
 	}
 );
 
@@ -136,6 +240,15 @@ decl_error! {
         TransferToSelf,
         NotForSale,
 		PriceTooLow,
+		TooManyKitties,
+		EmptySwap,
+		SwapNotExists,
+		NotSwapMaker,
+		NotSwapTaker,
+		SwapOffersCountOverflow,
+		KittyLocked,
+		KittyNotLocked,
+		DepositMissing,
 	}
 }
 
@@ -147,16 +260,21 @@ decl_module! {
 		// Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate::<T>()
+        }
+
         #[weight = 0]
+        #[transactional]
         pub fn create(origin) -> dispatch::DispatchResult {
             let sender = ensure_signed(origin)?;
-            let kitty_id = Self::next_kitty_id()?;
+            let kitty_id = Self::allocate_kitty_id()?;
             let dna = Self::random_value(&sender);
 
             let kitty = Kitty::new().set_self_dna(dna);
 
             // stake token
-            T::Currency::reserve(&sender, T::NewKittyReserve::get()).map_err(|_| Error::<T>::BalanceNotEnough)?;
+            Self::reserve_deposit(&sender, kitty_id)?;
 
             Self::insert_kitty(&sender, kitty_id, kitty);
             Self::deposit_event(RawEvent::Created(sender, kitty_id));
@@ -165,8 +283,10 @@ decl_module! {
         }
 
         #[weight = 0]
-        pub fn transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        #[transactional]
+        pub fn transfer(origin, to: <T::Lookup as StaticLookup>::Source, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
             let sender = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
 
             // kitty must exist
             let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::KittyNotExists)?;
@@ -175,13 +295,125 @@ decl_module! {
             // can't transfer to self
             ensure!(to != sender, Error::<T>::TransferToSelf);
 
-            <KittyOwners<T>>::insert(kitty_id, &to);
+            Self::do_transfer(&sender, &to, kitty_id)?;
 
             Self::deposit_event(RawEvent::Transferred(sender, to, kitty_id));
 
             Ok(())
         }
 
+        /// Transfer several kitties to the same account. Either all of them move or none.
+        #[weight = 0]
+        #[transactional]
+        pub fn transfer_batch(
+            origin,
+            to: <T::Lookup as StaticLookup>::Source,
+            kitty_ids: vec::Vec<T::KittyIndex>,
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            ensure!(kitty_ids.len() as u32 <= T::MaxBatchSize::get(), Error::<T>::TooManyKitties);
+            ensure!(to != sender, Error::<T>::TransferToSelf);
+
+            for kitty_id in kitty_ids {
+                // a duplicated id fails here because the kitty already left `sender`
+                Self::ensure_kitty_owner(&sender, kitty_id)?;
+                Self::do_transfer(&sender, &to, kitty_id)?;
+                Self::deposit_event(RawEvent::Transferred(sender.clone(), to.clone(), kitty_id));
+            }
+
+            Ok(())
+        }
+
+        /// Offer `offered` kitties, plus an optional balance, for `taker`'s `wanted` kitties.
+        #[weight = 0]
+        pub fn offer_swap(
+            origin,
+            taker: <T::Lookup as StaticLookup>::Source,
+            offered: vec::Vec<T::KittyIndex>,
+            wanted: vec::Vec<T::KittyIndex>,
+            balance: Option<BalanceOf<T>>,
+        ) -> dispatch::DispatchResult {
+            let maker = ensure_signed(origin)?;
+            let taker = T::Lookup::lookup(taker)?;
+
+            ensure!(taker != maker, Error::<T>::TransferToSelf);
+            ensure!(!offered.is_empty() || !wanted.is_empty(), Error::<T>::EmptySwap);
+            ensure!(offered.len() as u32 <= T::MaxBatchSize::get(), Error::<T>::TooManyKitties);
+            ensure!(wanted.len() as u32 <= T::MaxBatchSize::get(), Error::<T>::TooManyKitties);
+
+            for &kitty_id in offered.iter() {
+                Self::ensure_kitty_owner(&maker, kitty_id)?;
+            }
+            for &kitty_id in wanted.iter() {
+                Self::ensure_kitty_owner(&taker, kitty_id)?;
+            }
+
+            let swap_id = Self::swap_offers_count();
+            let next_swap_id = swap_id.checked_add(1).ok_or(Error::<T>::SwapOffersCountOverflow)?;
+
+            for &kitty_id in offered.iter().chain(wanted.iter()) {
+                <SwapOfferedKitties<T>>::mutate(kitty_id, |count| *count = count.saturating_add(1));
+            }
+            <SwapOffers<T>>::insert(swap_id, SwapOffer {
+                maker: maker.clone(),
+                taker: taker.clone(),
+                offered,
+                wanted,
+                balance,
+            });
+            SwapOffersCount::put(next_swap_id);
+
+            Self::deposit_event(RawEvent::SwapOffered(maker, taker, swap_id));
+
+            Ok(())
+        }
+
+        /// Withdraw a swap offer that has not been accepted yet.
+        #[weight = 0]
+        pub fn cancel_swap(origin, swap_id: SwapIndex) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let offer = Self::swap_offer(swap_id).ok_or(Error::<T>::SwapNotExists)?;
+            ensure!(offer.maker == sender, Error::<T>::NotSwapMaker);
+
+            Self::remove_swap_offer(swap_id, &offer);
+            Self::deposit_event(RawEvent::SwapCancelled(sender, swap_id));
+
+            Ok(())
+        }
+
+        /// Accept a swap offer. Every kitty and the balance move within this call, or nothing does.
+        #[weight = 0]
+        #[transactional]
+        pub fn accept_swap(origin, swap_id: SwapIndex) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let offer = Self::swap_offer(swap_id).ok_or(Error::<T>::SwapNotExists)?;
+            ensure!(offer.taker == sender, Error::<T>::NotSwapTaker);
+
+            // ownership may have changed since the offer was made
+            for &kitty_id in offer.offered.iter() {
+                Self::ensure_kitty_owner(&offer.maker, kitty_id)?;
+                Self::do_transfer(&offer.maker, &offer.taker, kitty_id)?;
+                Self::deposit_event(RawEvent::Transferred(offer.maker.clone(), offer.taker.clone(), kitty_id));
+            }
+            for &kitty_id in offer.wanted.iter() {
+                Self::ensure_kitty_owner(&offer.taker, kitty_id)?;
+                Self::do_transfer(&offer.taker, &offer.maker, kitty_id)?;
+                Self::deposit_event(RawEvent::Transferred(offer.taker.clone(), offer.maker.clone(), kitty_id));
+            }
+            if let Some(balance) = offer.balance {
+                T::Currency::transfer(&offer.maker, &offer.taker, balance, ExistenceRequirement::KeepAlive)?;
+            }
+
+            Self::remove_swap_offer(swap_id, &offer);
+            Self::deposit_event(RawEvent::SwapAccepted(offer.maker, offer.taker, swap_id));
+
+            Ok(())
+        }
+
         /// Set a price for a kitty for sale
 		/// None to delist the kitty
         #[weight = 0]
@@ -198,7 +430,8 @@ decl_module! {
         
         /// Buy a kitty
 		#[weight = 0]
-		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>) {
+		#[transactional]
+		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
 
 			let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
@@ -206,14 +439,27 @@ decl_module! {
 
 			ensure!(price >= kitty_price, Error::<T>::PriceTooLow);
 
-			T::Currency::transfer(&sender, &owner, kitty_price, ExistenceRequirement::KeepAlive)?;
-
-			<KittyPrices<T>>::remove(kitty_id);
-			<KittyOwners<T>>::insert(kitty_id, sender.clone());
+			// the seller gets the price minus the market's cut
+			let commission = T::MarketCommission::get() * kitty_price;
+			T::Currency::transfer(&sender, &owner, kitty_price - commission, ExistenceRequirement::KeepAlive)?;
+			if !commission.is_zero() {
+				let imbalance = T::Currency::withdraw(
+					&sender,
+					commission,
+					WithdrawReason::Transfer.into(),
+					ExistenceRequirement::KeepAlive,
+				)?;
+				T::OnCommission::on_unbalanced(imbalance);
+			}
+
+			Self::do_transfer(&owner, &sender, kitty_id)?;
 			Self::deposit_event(RawEvent::Sold(owner, sender, kitty_id, kitty_price));
+
+			Ok(())
 		}
 
         #[weight = 0]
+        #[transactional]
         pub fn breed(origin, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> dispatch::DispatchResult {
             let sender = ensure_signed(origin)?;
             let new_kitty_id = Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
@@ -221,6 +467,78 @@ decl_module! {
 
             Ok(())
         }
+
+        /// Destroy a kitty and release its deposit.
+        #[weight = 0]
+        pub fn burn(origin, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            Self::ensure_kitty_owner(&sender, kitty_id)?;
+            ensure!(!Self::kitty_locked(kitty_id), Error::<T>::KittyLocked);
+
+            // whatever is left of the deposit after a slash is released
+            if let Some((depositor, amount)) = <KittyDeposits<T>>::take(kitty_id) {
+                T::Currency::unreserve(&depositor, amount);
+            }
+
+            <Kitties<T>>::remove(kitty_id);
+            <KittyOwners<T>>::remove(kitty_id);
+            <KittyPrices<T>>::remove(kitty_id);
+            Self::remove_kitty_from_owner(&sender, kitty_id);
+
+            T::OnKittyBurn::on_kitty_burn(&sender, kitty_id);
+
+            // ids still referenced by lineage records or open swap offers are never reused,
+            // otherwise a new kitty would inherit parents, children or offers it never had
+            let has_lineage = <KittyParents<T>>::contains_key(kitty_id) || <KittyPartner<T>>::contains_key(kitty_id);
+            let in_swap = Self::swap_offered_kitties(kitty_id) > 0;
+            if T::ReuseKittyIds::get() && !has_lineage && !in_swap {
+                Self::free_kitty_id(kitty_id);
+            }
+
+            Self::deposit_event(RawEvent::Burned(sender, kitty_id));
+
+            Ok(())
+        }
+
+        /// Mint a kitty with the given DNA to `owner`.
+        ///
+        /// The owner never asked for it, so no deposit is taken. The kitty moves and burns
+        /// without one, like any kitty that has no deposit recorded.
+        #[weight = 0]
+        #[transactional]
+        pub fn mint(origin, owner: <T::Lookup as StaticLookup>::Source, dna: DNA) -> dispatch::DispatchResult {
+            T::MintOrigin::ensure_origin(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            let kitty_id = Self::allocate_kitty_id()?;
+
+            Self::insert_kitty(&owner, kitty_id, Kitty::new().set_self_dna(dna));
+            Self::deposit_event(RawEvent::Created(owner, kitty_id));
+
+            Ok(())
+        }
+
+        /// Move a kitty to `to` whoever owns it, e.g. to recover a stolen kitty.
+        #[weight = 0]
+        #[transactional]
+        pub fn force_transfer(
+            origin,
+            to: <T::Lookup as StaticLookup>::Source,
+            kitty_id: T::KittyIndex,
+        ) -> dispatch::DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+            let to = T::Lookup::lookup(to)?;
+
+            let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::KittyNotExists)?;
+            ensure!(to != owner, Error::<T>::TransferToSelf);
+
+            Self::do_transfer(&owner, &to, kitty_id)?;
+
+            Self::deposit_event(RawEvent::Transferred(owner, to, kitty_id));
+
+            Ok(())
+        }
 	}
 }
 
@@ -231,21 +549,123 @@ fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8 {
 impl<T: Trait> Module<T> {
     fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex, kitty: Kitty) {
         <Kitties<T>>::insert(kitty_id, kitty);
-        <KittiesCount<T>>::put(kitty_id + 1.into());
         <KittyOwners<T>>::insert(kitty_id, owner);
+        Self::add_kitty_to_owner(owner, kitty_id);
+    }
+
+    fn ensure_kitty_owner(who: &T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        let owner = Self::kitty_owner(kitty_id).ok_or(Error::<T>::KittyNotExists)?;
+        ensure!(owner == *who, Error::<T>::NotKittyOwner);
+        Ok(())
+    }
+
+    /// Reserve the creation deposit of a new kitty from `owner` and record it.
+    fn reserve_deposit(owner: &T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        let amount = T::NewKittyReserve::get();
+        T::Currency::reserve(owner, amount).map_err(|_| Error::<T>::BalanceNotEnough)?;
+        <KittyDeposits<T>>::insert(kitty_id, (owner.clone(), amount));
+        Ok(())
+    }
 
-        if <KittyTotal<T>>::contains_key(&owner) {
-            let _ = <KittyTotal<T>>::mutate(owner, |val| val.push(kitty_id));
+    /// Move the recorded deposit of a kitty to `to`. Kitties without a deposit move for free.
+    ///
+    /// Callers must be transactional, a fresh `to` is funded in several steps.
+    fn move_deposit(to: &T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        let (depositor, amount) = match Self::kitty_deposit(kitty_id) {
+            Some(deposit) => deposit,
+            None => return Ok(()),
+        };
+
+        if T::Currency::total_balance(to).is_zero() {
+            // `repatriate_reserved` refuses accounts that don't exist yet
+            ensure!(T::Currency::unreserve(&depositor, amount).is_zero(), Error::<T>::DepositMissing);
+            T::Currency::transfer(&depositor, to, amount, ExistenceRequirement::AllowDeath)?;
+            T::Currency::reserve(to, amount)?;
         } else {
-            <KittyTotal<T>>::insert(owner, vec![kitty_id]);
+            let leftover = T::Currency::repatriate_reserved(&depositor, to, amount, BalanceStatus::Reserved)?;
+            ensure!(leftover.is_zero(), Error::<T>::DepositMissing);
         }
+
+        <KittyDeposits<T>>::insert(kitty_id, (to.clone(), amount));
+        Ok(())
+    }
+
+    /// Move a kitty together with its creation deposit. Any ask on it is cleared.
+    ///
+    /// Callers must be transactional, see `move_deposit`.
+    fn do_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        ensure!(!Self::kitty_locked(kitty_id), Error::<T>::KittyLocked);
+
+        Self::move_deposit(to, kitty_id)?;
+
+        Self::remove_kitty_from_owner(from, kitty_id);
+        Self::add_kitty_to_owner(to, kitty_id);
+        <KittyOwners<T>>::insert(kitty_id, to);
+        <KittyPrices<T>>::remove(kitty_id);
+
+        T::OnKittyTransfer::on_kitty_transfer(from, to, kitty_id);
+
+        Ok(())
+    }
+
+    fn remove_swap_offer(swap_id: SwapIndex, offer: &SwapOffer<T::AccountId, T::KittyIndex, BalanceOf<T>>) {
+        for &kitty_id in offer.offered.iter().chain(offer.wanted.iter()) {
+            <SwapOfferedKitties<T>>::mutate_exists(kitty_id, |count| {
+                *count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+            });
+        }
+        <SwapOffers<T>>::remove(swap_id);
+    }
+
+    pub(crate) fn add_kitty_to_owner(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        let count = Self::owned_kitties_count(owner);
+
+        <OwnedKittiesArray<T>>::insert(owner, count, kitty_id);
+        <OwnedKittiesIndex<T>>::insert(kitty_id, count);
+        <OwnedKittiesCount<T>>::insert(owner, count + One::one());
+    }
+
+    fn remove_kitty_from_owner(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        let last = Self::owned_kitties_count(owner).saturating_sub(One::one());
+        let index = <OwnedKittiesIndex<T>>::take(kitty_id);
+
+        // move the last kitty into the freed slot so positions stay contiguous
+        if index != last {
+            if let Some(last_kitty_id) = <OwnedKittiesArray<T>>::get(owner, last) {
+                <OwnedKittiesArray<T>>::insert(owner, index, last_kitty_id);
+                <OwnedKittiesIndex<T>>::insert(last_kitty_id, index);
+            }
+        }
+
+        <OwnedKittiesArray<T>>::remove(owner, last);
+        if last.is_zero() {
+            <OwnedKittiesCount<T>>::remove(owner);
+        } else {
+            <OwnedKittiesCount<T>>::insert(owner, last);
+        }
+    }
+
+    /// Kitties of `owner` at positions `start..start + limit`, for paging through large inventories.
+    pub fn owned_kitties(owner: &T::AccountId, start: T::KittyIndex, limit: u32) -> vec::Vec<T::KittyIndex> {
+        let end = Self::owned_kitties_count(owner).min(start.saturating_add(limit.into()));
+        let mut kitties = vec::Vec::new();
+        let mut index = start;
+
+        while index < end {
+            if let Some(kitty_id) = <OwnedKittiesArray<T>>::get(owner, index) {
+                kitties.push(kitty_id);
+            }
+            index += One::one();
+        }
+
+        kitties
     }
 
     fn update_kitty_parents(children: T::KittyIndex, father: T::KittyIndex, mother: T::KittyIndex) {
             <KittyParents<T>>::insert(children, (father, mother));
     }
 
-    fn update_kitty_children(children: T::KittyIndex, father: T::KittyIndex, mother: T::KittyIndex) {
+    pub(crate) fn update_kitty_children(children: T::KittyIndex, father: T::KittyIndex, mother: T::KittyIndex) {
         if <KittyChildren<T>>::contains_key(father, mother) {
             let _ = <KittyChildren<T>>::mutate(father, mother, |val| val.push(children));
         } else {
@@ -253,7 +673,7 @@ impl<T: Trait> Module<T> {
         }
     }
 
-    fn update_kitty_brother(kitty_id: T::KittyIndex) {
+    pub(crate) fn update_kitty_brother(kitty_id: T::KittyIndex) {
         let (father, mother) = <KittyParents<T>>::get(kitty_id);
 
         if <KittyChildren<T>>::contains_key(father, mother) {
@@ -270,15 +690,42 @@ impl<T: Trait> Module<T> {
         <KittyPartner<T>>::insert(partner1, partner2);
     }
 
-    fn next_kitty_id() -> sp_std::result::Result<T::KittyIndex, DispatchError> {
-        let kitty_id = Self::kitties_count();
-        if kitty_id == <T as Trait>::KittyIndex::max_value() {
-            return Err(Error::<T>::KittiesCountOverflow.into());
+    /// Take an id off the free list, or the next fresh id if reuse is off or the list is empty.
+    ///
+    /// Callers must be transactional, the id is consumed even if they fail afterwards.
+    fn allocate_kitty_id() -> sp_std::result::Result<T::KittyIndex, DispatchError> {
+        if T::ReuseKittyIds::get() {
+            if let Some(kitty_id) = Self::pop_free_kitty_id() {
+                return Ok(kitty_id);
+            }
         }
 
+        let kitty_id = Self::kitties_count();
+        let next_kitty_id = kitty_id.checked_add(&One::one()).ok_or(Error::<T>::KittiesCountOverflow)?;
+        <KittiesCount<T>>::put(next_kitty_id);
+
         Ok(kitty_id)
     }
 
+    fn free_kitty_id(kitty_id: T::KittyIndex) {
+        let count = Self::free_kitty_ids_count();
+
+        <FreeKittyIds<T>>::insert(count, kitty_id);
+        // can't overflow, there are fewer free ids than ids ever allocated
+        <FreeKittyIdsCount<T>>::put(count.saturating_add(One::one()));
+    }
+
+    fn pop_free_kitty_id() -> Option<T::KittyIndex> {
+        let count = Self::free_kitty_ids_count();
+        if count.is_zero() {
+            return None;
+        }
+
+        let last = count - One::one();
+        <FreeKittyIdsCount<T>>::put(last);
+        <FreeKittyIds<T>>::take(last)
+    }
+
     fn random_value(sender: &T::AccountId) -> DNA {
         let payload = (
             T::Randomness::random_seed(),
@@ -304,7 +751,7 @@ impl<T: Trait> Module<T> {
         ensure!(owner1 == *sender, Error::<T>::NotKittyOwner);
         ensure!(owner2 == *sender, Error::<T>::NotKittyOwner);
 
-        let kitty_id = Self::next_kitty_id()?;
+        let kitty_id = Self::allocate_kitty_id()?;
 
         // update kitty partner
         Self::update_kitty_partner(kitty_id_1, kitty_id_2);
@@ -314,7 +761,7 @@ impl<T: Trait> Module<T> {
         Self::update_kitty_parents(kitty_id, kitty_id_1, kitty_id_2);
 
         // update kitty children
-        Self::update_kitty_children(kitty_id, kitty_id_2, kitty_id_2);
+        Self::update_kitty_children(kitty_id, kitty_id_1, kitty_id_2);
 
 
         // update kitty brother
@@ -333,9 +780,64 @@ impl<T: Trait> Module<T> {
         let new_kitty = Kitty::new().set_self_dna(new_dna);
 
         // stake token
-        T::Currency::reserve(&sender, T::NewKittyReserve::get()).map_err(|_| Error::<T>::BalanceNotEnough)?;
+        Self::reserve_deposit(sender, kitty_id)?;
 
         Self::insert_kitty(sender, kitty_id, new_kitty);
         Ok(kitty_id)
     }
 }
+
+impl<T: Trait> NftInspect<T::AccountId> for Module<T> {
+    type KittyIndex = T::KittyIndex;
+
+    fn owner_of(kitty_id: T::KittyIndex) -> Option<T::AccountId> {
+        Self::kitty_owner(kitty_id)
+    }
+
+    fn dna_of(kitty_id: T::KittyIndex) -> Option<DNA> {
+        Self::kitties(kitty_id).map(|kitty| kitty.get_self_dna())
+    }
+
+    fn parents_of(kitty_id: T::KittyIndex) -> Option<(T::KittyIndex, T::KittyIndex)> {
+        if <KittyParents<T>>::contains_key(kitty_id) {
+            Some(Self::kitty_parents(kitty_id))
+        } else {
+            None
+        }
+    }
+
+    fn children_of(father: T::KittyIndex, mother: T::KittyIndex) -> vec::Vec<T::KittyIndex> {
+        Self::kitty_children(father, mother)
+    }
+
+    fn is_locked(kitty_id: T::KittyIndex) -> bool {
+        Self::kitty_locked(kitty_id)
+    }
+}
+
+impl<T: Trait> NftTransfer<T::AccountId> for Module<T> {
+    fn transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        Self::ensure_kitty_owner(from, kitty_id)?;
+        ensure!(to != from, Error::<T>::TransferToSelf);
+
+        Self::do_transfer(from, to, kitty_id)?;
+        Self::deposit_event(RawEvent::Transferred(from.clone(), to.clone(), kitty_id));
+
+        Ok(())
+    }
+
+    fn lock(kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        ensure!(<KittyOwners<T>>::contains_key(kitty_id), Error::<T>::KittyNotExists);
+        ensure!(!Self::kitty_locked(kitty_id), Error::<T>::KittyLocked);
+
+        <LockedKitties<T>>::insert(kitty_id, true);
+        Ok(())
+    }
+
+    fn unlock(kitty_id: T::KittyIndex) -> dispatch::DispatchResult {
+        ensure!(Self::kitty_locked(kitty_id), Error::<T>::KittyNotLocked);
+
+        <LockedKitties<T>>::remove(kitty_id);
+        Ok(())
+    }
+}
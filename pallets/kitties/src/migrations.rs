@@ -0,0 +1,347 @@
+//! Storage migrations for the kitties pallet.
+//!
+//! `migrate` runs from `on_runtime_upgrade` and walks `StorageVersion` up to `Releases::LATEST`.
+//! `pre_migrate` and `post_migrate` are meant for dry runs against exported state, they read
+//! the whole pallet storage and must not be called on chain.
+
+use codec::Decode;
+use frame_support::{
+    ensure, StorageValue, StorageMap, StorageDoubleMap, IterableStorageMap,
+    storage::{unhashed, migration::{StorageIterator, take_storage_value}},
+    traits::Get,
+    weights::Weight,
+};
+use sp_runtime::traits::One;
+use sp_std::prelude::*;
+use crate::*;
+
+const MODULE: &[u8] = b"Kitties";
+/// Length of the hash in front of a `blake2_128_concat` key.
+const BLAKE2_128_LEN: usize = 16;
+/// Length of the hash in front of a `twox_64_concat` key.
+const TWOX_64_LEN: usize = 8;
+
+/// Bring storage up to `Releases::LATEST`.
+pub fn migrate<T: Trait>() -> Weight {
+    let mut weight = T::DbWeight::get().reads(1);
+
+    if Module::<T>::storage_version() < Releases::V2_0_0 {
+        weight = weight.saturating_add(migrate_to_v2::<T>());
+        StorageVersion::put(Releases::V2_0_0);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+
+    // Kitties created before `KittyDeposits` existed keep no recorded deposit. Their
+    // `NewKittyReserve` was never moved on transfer, so it sits with whoever created the kitty,
+    // which storage doesn't record. Reserves of the current owners can't be attributed either,
+    // they also hold claim, identity and democracy deposits. Transfers, sales and burns of
+    // these kitties move no reserve.
+    if Module::<T>::storage_version() < Releases::V3_0_0 {
+        StorageVersion::put(Releases::V3_0_0);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+
+    if Module::<T>::storage_version() < Releases::V4_0_0 {
+        weight = weight.saturating_add(migrate_to_v4::<T>());
+        StorageVersion::put(Releases::V4_0_0);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+
+    if Module::<T>::storage_version() < Releases::V5_0_0 {
+        weight = weight.saturating_add(migrate_to_v5::<T>());
+        StorageVersion::put(Releases::V5_0_0);
+        weight = weight.saturating_add(T::DbWeight::get().writes(1));
+    }
+
+    weight
+}
+
+/// Replace the old `KittyTotal` vectors with the owner index.
+///
+/// `KittyTotal` was never updated on transfer or sale, so the index is rebuilt from
+/// `KittyOwners` instead of copying the vectors over. Chains that migrated before
+/// `StorageVersion` existed have no `KittyTotal` left and are skipped.
+fn migrate_to_v2<T: Trait>() -> Weight {
+    // values are not needed and may have been written with a narrower index type
+    let old_entries = StorageIterator::<()>::new(MODULE, b"KittyTotal")
+        .drain()
+        .count() as Weight;
+
+    // nothing left to migrate
+    if old_entries == 0 {
+        return T::DbWeight::get().reads(1);
+    }
+
+    let mut kitties: Weight = 0;
+    for (kitty_id, owner) in <KittyOwners<T>>::iter() {
+        Module::<T>::add_kitty_to_owner(&owner, kitty_id);
+        kitties += 1;
+    }
+
+    T::DbWeight::get().reads_writes(old_entries + kitties * 2, old_entries + kitties * 3)
+}
+
+/// Rebuild `KittyChildren` and `KittyBrother` from `KittyParents`.
+///
+/// `breed` used to file every child under `(mother, mother)`, and brothers were read from
+/// that wrong entry. Children are replayed in id order, bred ids are never reused, so each
+/// kitty again gets the older siblings of the same pair as brothers.
+fn migrate_to_v4<T: Trait>() -> Weight {
+    let old_entries = StorageIterator::<()>::new(MODULE, b"KittyChildren")
+        .drain()
+        .count() as Weight;
+
+    let mut bred: Vec<(T::KittyIndex, (T::KittyIndex, T::KittyIndex))> = <KittyParents<T>>::iter().collect();
+    bred.sort_by_key(|(kitty_id, _)| *kitty_id);
+
+    for &(kitty_id, (father, mother)) in bred.iter() {
+        Module::<T>::update_kitty_children(kitty_id, father, mother);
+        Module::<T>::update_kitty_brother(kitty_id);
+    }
+
+    let kitties = bred.len() as Weight;
+    T::DbWeight::get().reads_writes(old_entries + kitties * 3, old_entries + kitties * 2)
+}
+
+/// Count the open swap offers naming each kitty in `SwapOfferedKitties`.
+fn migrate_to_v5<T: Trait>() -> Weight {
+    let mut offers: Weight = 0;
+    let mut kitties: Weight = 0;
+
+    for (_, offer) in <SwapOffers<T>>::iter() {
+        offers += 1;
+        for &kitty_id in offer.offered.iter().chain(offer.wanted.iter()) {
+            <SwapOfferedKitties<T>>::mutate(kitty_id, |count| *count = count.saturating_add(1));
+            kitties += 1;
+        }
+    }
+
+    T::DbWeight::get().reads_writes(offers + kitties, kitties)
+}
+
+/// Checks before `migrate`: storage must not be newer than this runtime.
+///
+/// Entries are not decoded here, they may still use a layout the migrations are about to fix.
+pub fn pre_migrate<T: Trait>() -> Result<(), &'static str> {
+    ensure!(Module::<T>::storage_version() <= Releases::LATEST, "kitties storage is newer than this runtime");
+    Ok(())
+}
+
+/// Checks after `migrate`: storage is at the latest version and the owner index matches `KittyOwners`.
+pub fn post_migrate<T: Trait>() -> Result<(), &'static str> {
+    ensure!(Module::<T>::storage_version() == Releases::LATEST, "kitties storage version was not bumped");
+    ensure!(
+        StorageIterator::<()>::new(MODULE, b"KittyTotal").next().is_none(),
+        "KittyTotal was not removed"
+    );
+    ensure_all_decode::<T>()?;
+
+    let mut owned: Vec<(T::AccountId, T::KittyIndex)> = Vec::new();
+    for (kitty_id, owner) in <KittyOwners<T>>::iter() {
+        let position = <OwnedKittiesIndex<T>>::get(kitty_id);
+        ensure!(
+            <OwnedKittiesArray<T>>::get(&owner, position) == Some(kitty_id),
+            "kitty missing from its owner's index"
+        );
+        match owned.iter_mut().find(|(account, _)| *account == owner) {
+            Some((_, count)) => *count += One::one(),
+            None => owned.push((owner, One::one())),
+        }
+    }
+    for (owner, count) in owned {
+        ensure!(<OwnedKittiesCount<T>>::get(&owner) == count, "owned kitties count mismatch");
+    }
+
+    for (kitty_id, (father, mother)) in <KittyParents<T>>::iter() {
+        ensure!(
+            <KittyChildren<T>>::get(father, mother).contains(&kitty_id),
+            "kitty missing from its parents' children"
+        );
+    }
+
+    for (kitty_id, (depositor, _)) in <KittyDeposits<T>>::iter() {
+        ensure!(
+            <KittyOwners<T>>::get(kitty_id) == Some(depositor),
+            "kitty deposit not held by the kitty's owner"
+        );
+    }
+
+    Ok(())
+}
+
+/// Every raw entry of `Kitties` and `KittyOwners` must decode with the current types.
+fn ensure_all_decode<T: Trait>() -> Result<(), &'static str> {
+    ensure!(
+        StorageIterator::<()>::new(MODULE, b"Kitties").count() == <Kitties<T>>::iter().count(),
+        "some kitties don't decode"
+    );
+    ensure!(
+        StorageIterator::<()>::new(MODULE, b"KittyOwners").count() == <KittyOwners<T>>::iter().count(),
+        "some kitty owners don't decode"
+    );
+
+    Ok(())
+}
+
+/// Remove every entry of `item`, returning the raw hashed keys with their old values.
+///
+/// Entries are collected before anything is written back, so re-inserted keys are never
+/// visited again with the old value type.
+fn take_all<V: Decode>(item: &[u8]) -> Vec<(Vec<u8>, V)> {
+    StorageIterator::<V>::new(MODULE, item).drain().collect()
+}
+
+/// Decode one concat-hashed key and advance `raw` past it.
+fn decode_key<K: Decode>(raw: &mut &[u8], hash_len: usize) -> Option<K> {
+    if raw.len() < hash_len {
+        return None;
+    }
+    *raw = &raw[hash_len..];
+    K::decode(raw).ok()
+}
+
+/// Encoded length of `KittiesCount`, which is the width of the kitty index currently in storage.
+pub fn stored_index_width<T: Trait>() -> Option<usize> {
+    unhashed::get_raw(&<KittiesCount<T>>::hashed_key()).map(|raw| raw.len())
+}
+
+/// Re-encode every kitty index in storage after `T::KittyIndex` was widened from `OldIndex`.
+///
+/// Must only run once, guard it with `stored_index_width`.
+pub fn widen_kitty_index<T, OldIndex>() -> Weight where
+    T: Trait,
+    OldIndex: Decode + Into<T::KittyIndex>,
+{
+    let widen = |ids: Vec<OldIndex>| -> Vec<T::KittyIndex> { ids.into_iter().map(Into::into).collect() };
+    let mut entries: Weight = 0;
+
+    if let Some(count) = take_storage_value::<OldIndex>(MODULE, b"KittiesCount", &[]) {
+        <KittiesCount<T>>::put(count.into());
+        entries += 1;
+    }
+    if let Some(count) = take_storage_value::<OldIndex>(MODULE, b"FreeKittyIdsCount", &[]) {
+        <FreeKittyIdsCount<T>>::put(count.into());
+        entries += 1;
+    }
+
+    for (key, kitty_id) in take_all::<OldIndex>(b"FreeKittyIds") {
+        if let Some(position) = decode_key::<OldIndex>(&mut &key[..], TWOX_64_LEN) {
+            <FreeKittyIds<T>>::insert(position.into(), kitty_id.into());
+            entries += 1;
+        }
+    }
+
+    for (key, kitty) in take_all::<Kitty>(b"Kitties") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <Kitties<T>>::insert(kitty_id.into(), kitty);
+            entries += 1;
+        }
+    }
+
+    for (key, owner) in take_all::<T::AccountId>(b"KittyOwners") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyOwners<T>>::insert(kitty_id.into(), owner);
+            entries += 1;
+        }
+    }
+
+    for (key, count) in take_all::<OldIndex>(b"OwnedKittiesCount") {
+        if let Some(owner) = decode_key::<T::AccountId>(&mut &key[..], BLAKE2_128_LEN) {
+            <OwnedKittiesCount<T>>::insert(owner, count.into());
+            entries += 1;
+        }
+    }
+
+    for (key, kitty_id) in take_all::<OldIndex>(b"OwnedKittiesArray") {
+        let raw = &mut &key[..];
+        if let (Some(owner), Some(position)) = (
+            decode_key::<T::AccountId>(raw, BLAKE2_128_LEN),
+            decode_key::<OldIndex>(raw, TWOX_64_LEN),
+        ) {
+            <OwnedKittiesArray<T>>::insert(owner, position.into(), kitty_id.into());
+            entries += 1;
+        }
+    }
+
+    for (key, position) in take_all::<OldIndex>(b"OwnedKittiesIndex") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <OwnedKittiesIndex<T>>::insert(kitty_id.into(), position.into());
+            entries += 1;
+        }
+    }
+
+    for (key, (father, mother)) in take_all::<(OldIndex, OldIndex)>(b"KittyParents") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyParents<T>>::insert(kitty_id.into(), (father.into(), mother.into()));
+            entries += 1;
+        }
+    }
+
+    for (key, children) in take_all::<Vec<OldIndex>>(b"KittyChildren") {
+        let raw = &mut &key[..];
+        if let (Some(father), Some(mother)) = (
+            decode_key::<OldIndex>(raw, BLAKE2_128_LEN),
+            decode_key::<OldIndex>(raw, BLAKE2_128_LEN),
+        ) {
+            <KittyChildren<T>>::insert(father.into(), mother.into(), widen(children));
+            entries += 1;
+        }
+    }
+
+    for (key, brothers) in take_all::<Vec<OldIndex>>(b"KittyBrother") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyBrother<T>>::insert(kitty_id.into(), widen(brothers));
+            entries += 1;
+        }
+    }
+
+    for (key, partner) in take_all::<OldIndex>(b"KittyPartner") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyPartner<T>>::insert(kitty_id.into(), partner.into());
+            entries += 1;
+        }
+    }
+
+    for (key, deposit) in take_all::<(T::AccountId, BalanceOf<T>)>(b"KittyDeposits") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyDeposits<T>>::insert(kitty_id.into(), deposit);
+            entries += 1;
+        }
+    }
+
+    for (key, count) in take_all::<u32>(b"SwapOfferedKitties") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <SwapOfferedKitties<T>>::insert(kitty_id.into(), count);
+            entries += 1;
+        }
+    }
+
+    for (key, locked) in take_all::<bool>(b"LockedKitties") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <LockedKitties<T>>::insert(kitty_id.into(), locked);
+            entries += 1;
+        }
+    }
+
+    for (key, price) in take_all::<BalanceOf<T>>(b"KittyPrices") {
+        if let Some(kitty_id) = decode_key::<OldIndex>(&mut &key[..], BLAKE2_128_LEN) {
+            <KittyPrices<T>>::insert(kitty_id.into(), price);
+            entries += 1;
+        }
+    }
+
+    for (key, offer) in take_all::<SwapOffer<T::AccountId, OldIndex, BalanceOf<T>>>(b"SwapOffers") {
+        if let Some(swap_id) = decode_key::<SwapIndex>(&mut &key[..], TWOX_64_LEN) {
+            <SwapOffers<T>>::insert(swap_id, SwapOffer {
+                maker: offer.maker,
+                taker: offer.taker,
+                offered: widen(offer.offered),
+                wanted: widen(offer.wanted),
+                balance: offer.balance,
+            });
+            entries += 1;
+        }
+    }
+
+    T::DbWeight::get().reads_writes(entries, entries * 2)
+}
@@ -1,7 +1,8 @@
-use crate::{Module, Trait};
+use crate::{Module, Trait, OnKittyTransfer};
+use std::cell::RefCell;
 use frame_support::{
     impl_outer_event, impl_outer_origin, parameter_types,
-    traits::{OnFinalize, OnInitialize},
+    traits::{Get, OnFinalize, OnInitialize, OnUnbalanced, Currency},
     weights::Weight,
 };
 use frame_system as system;
@@ -9,7 +10,7 @@ use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
+    Perbill, Permill,
 };
 
 use balances;
@@ -83,17 +84,64 @@ type Randomness = pallet_randomness_collective_flip::Module<Test>;
 
 parameter_types! {
     pub const NewKittyReserve: u64 = 5_000;
+    pub const MaxBatchSize: u32 = 3;
+    pub const MarketCommission: Permill = Permill::from_percent(10);
+}
+
+/// Account collecting the market commission in tests.
+pub const COMMISSION_POT: u64 = 99;
+
+pub struct CommissionPot;
+impl OnUnbalanced<balances::NegativeImbalance<Test>> for CommissionPot {
+    fn on_nonzero_unbalanced(amount: balances::NegativeImbalance<Test>) {
+        Balances::resolve_creating(&COMMISSION_POT, amount);
+    }
+}
+
+thread_local! {
+    pub static KITTY_TRANSFERS: RefCell<Vec<(u64, u64, u8)>> = RefCell::new(vec![]);
+    static REUSE_KITTY_IDS: RefCell<bool> = RefCell::new(true);
+}
+
+pub struct ReuseKittyIds;
+impl ReuseKittyIds {
+    pub fn set(reuse: bool) {
+        REUSE_KITTY_IDS.with(|v| *v.borrow_mut() = reuse);
+    }
+}
+impl Get<bool> for ReuseKittyIds {
+    fn get() -> bool {
+        REUSE_KITTY_IDS.with(|v| *v.borrow())
+    }
+}
+
+/// Records every transfer reported through the `OnKittyTransfer` hook.
+pub struct RecordTransfers;
+impl OnKittyTransfer<u64, u8> for RecordTransfers {
+    fn on_kitty_transfer(from: &u64, to: &u64, kitty_id: u8) {
+        KITTY_TRANSFERS.with(|t| t.borrow_mut().push((*from, *to, kitty_id)));
+    }
 }
 
 impl Trait for Test {
     type Event = TestEvent;
-    type KittyIndex = u32;
+    // a narrow index makes the id boundary reachable in tests
+    type KittyIndex = u8;
     type Randomness = Randomness;
     type NewKittyReserve = NewKittyReserve;
     type Currency = balances::Module<Self>;
+    type MaxBatchSize = MaxBatchSize;
+    type OnKittyTransfer = RecordTransfers;
+    type OnKittyBurn = ();
+    type ReuseKittyIds = ReuseKittyIds;
+    type MintOrigin = system::EnsureRoot<u64>;
+    type ForceOrigin = system::EnsureRoot<u64>;
+    type MarketCommission = MarketCommission;
+    type OnCommission = CommissionPot;
 }
 
 pub type KittiesModule = Module<Test>;
+pub type Balances = balances::Module<Test>;
 pub type System = frame_system::Module<Test>;
 
 /// Run until a particular block.
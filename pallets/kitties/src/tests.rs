@@ -1,8 +1,13 @@
-use crate::{mock::*, Error, Event};
-use frame_support::{assert_noop, assert_ok};
+use crate::{mock::*, Error, Event, OwnedKittiesCount, OwnedKittiesArray, OwnedKittiesIndex, KittiesCount, KittyDeposits, KittyChildren, KittyBrother, SwapOfferedKitties, StorageVersion, NftInspect, NftTransfer, Releases, DNA};
+use crate::migrations::{stored_index_width, widen_kitty_index, pre_migrate, post_migrate};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, Blake2_128Concat, StorageHasher, StorageDoubleMap, StorageMap, StorageValue};
+use frame_support::storage::migration::{put_storage_value, StorageIterator};
+use frame_support::traits::{OnRuntimeUpgrade, ReservableCurrency};
 use frame_system::EventRecord;
 use frame_system::Phase;
 use balances::RawEvent;
+use sp_runtime::DispatchError;
 
 // create kitty
 #[test]
@@ -27,9 +32,9 @@ fn owner_kitties_transfer() {
         let _ = KittiesModule::create(Origin::signed(1));
         // test transfer
         assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
-        // check event
+        // check event, after the deposit follows the kitty
         assert_eq!(
-            System::events()[2].event,
+            System::events()[3].event,
             TestEvent::kitties_event(Event::<Test>::Transferred(1u64, 2, 0)),
         )
     })
@@ -173,4 +178,674 @@ fn kitties_breed_failed_when_not_owner() {
             Error::<Test>::NotKittyOwner
         );
     })
-}
\ No newline at end of file
+}
+
+// owner index follows transfers
+#[test]
+fn owned_kitties_index_follows_transfer() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+        assert_eq!(KittiesModule::owned_kitties_count(1), 2);
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![2, 1]);
+        assert_eq!(KittiesModule::owned_kitties_index(2), 0);
+        assert_eq!(KittiesModule::owned_kitties_count(2), 1);
+        assert_eq!(KittiesModule::owned_kitties(&2, 0, 10), vec![0]);
+    })
+}
+
+// paginate owned kitties
+#[test]
+fn owned_kitties_pagination() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        for _ in 0..5 {
+            let _ = KittiesModule::create(Origin::signed(1));
+        }
+
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 2), vec![0, 1]);
+        assert_eq!(KittiesModule::owned_kitties(&1, 2, 2), vec![2, 3]);
+        assert_eq!(KittiesModule::owned_kitties(&1, 4, 2), vec![4]);
+        assert_eq!(KittiesModule::owned_kitties(&1, 5, 2), Vec::<u8>::new());
+    })
+}
+
+// migrate the old KittyTotal vectors
+#[test]
+fn on_runtime_upgrade_migrates_kitty_total() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        // rewind to the old layout
+        for owner in &[1u64, 2] {
+            <OwnedKittiesCount<Test>>::remove(owner);
+            <OwnedKittiesArray<Test>>::remove_prefix(owner);
+        }
+        put_storage_value(b"Kitties", b"KittyTotal", &Blake2_128Concat::hash(&1u64.encode()), vec![0u8]);
+        put_storage_value(b"Kitties", b"KittyTotal", &Blake2_128Concat::hash(&2u64.encode()), vec![1u8]);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![0]);
+        assert_eq!(KittiesModule::owned_kitties(&2, 0, 10), vec![1]);
+        assert_eq!(
+            StorageIterator::<Vec<u8>>::new(b"Kitties", b"KittyTotal").count(),
+            0
+        );
+
+        // running it again is a no-op
+        KittiesModule::on_runtime_upgrade();
+        assert_eq!(KittiesModule::owned_kitties_count(1), 1);
+        assert_eq!(KittiesModule::storage_version(), Releases::LATEST);
+    })
+}
+
+// deposit follows the kitty
+#[test]
+fn transfer_moves_kitty_deposit() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        assert_ok!(KittiesModule::ask(Origin::signed(1), 0, Some(100)));
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 5000);
+        // the old ask does not survive the transfer
+        assert_eq!(KittiesModule::kitty_price(0), None);
+    })
+}
+
+// the deposit also reaches an account that doesn't exist yet
+#[test]
+fn transfer_to_fresh_account_moves_deposit() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 10, 0));
+
+        assert_eq!(KittiesModule::kitty_owner(0), Some(10));
+        assert_eq!(KittiesModule::kitty_deposit(0), Some((10, 5000)));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(10), 5000);
+        assert_eq!(Balances::free_balance(10), 0);
+        assert_eq!(Balances::free_balance(1), 100000000 - 5000);
+    })
+}
+
+// only the recorded deposit moves, other reserves stay put
+#[test]
+fn kitty_without_deposit_moves_no_reserve() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        <KittyDeposits<Test>>::remove(0);
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::reserved_balance(2), 0);
+
+        // and burning it releases nothing
+        assert_ok!(KittiesModule::burn(Origin::signed(2), 0));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::free_balance(2), 100000000);
+    })
+}
+
+// a deposit that is no longer reserved blocks the transfer
+#[test]
+fn transfer_failed_when_deposit_missing() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        Balances::unreserve(&1, 2000);
+
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 0), Error::<Test>::DepositMissing);
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 10, 0), Error::<Test>::DepositMissing);
+    })
+}
+
+// batch transfer
+#[test]
+fn transfer_batch_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        for _ in 0..3 {
+            let _ = KittiesModule::create(Origin::signed(1));
+        }
+
+        assert_ok!(KittiesModule::transfer_batch(Origin::signed(1), 2, vec![0, 2]));
+
+        assert_eq!(KittiesModule::kitty_owner(0), Some(2));
+        assert_eq!(KittiesModule::kitty_owner(1), Some(1));
+        assert_eq!(KittiesModule::kitty_owner(2), Some(2));
+        assert_eq!(KittiesModule::owned_kitties_count(1), 1);
+        assert_eq!(KittiesModule::owned_kitties_count(2), 2);
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::reserved_balance(2), 10000);
+    })
+}
+
+// test TooManyKitties
+#[test]
+fn transfer_batch_failed_when_too_long() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+
+        assert_noop!(
+            KittiesModule::transfer_batch(Origin::signed(1), 2, vec![0, 1, 2, 3]),
+            Error::<Test>::TooManyKitties
+        );
+    })
+}
+
+// a failing item reverts the whole batch
+#[test]
+fn transfer_batch_is_atomic() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        assert_noop!(
+            KittiesModule::transfer_batch(Origin::signed(1), 3, vec![0, 1]),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_noop!(
+            KittiesModule::transfer_batch(Origin::signed(1), 3, vec![0, 0]),
+            Error::<Test>::NotKittyOwner
+        );
+    })
+}
+
+// swap kitties plus balance
+#[test]
+fn swap_offer_accept_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(1), 2, vec![0, 1], vec![2], Some(300)));
+        assert_eq!(
+            System::events().last().unwrap().event,
+            TestEvent::kitties_event(Event::<Test>::SwapOffered(1u64, 2, 0)),
+        );
+
+        assert_noop!(KittiesModule::accept_swap(Origin::signed(3), 0), Error::<Test>::NotSwapTaker);
+        assert_ok!(KittiesModule::accept_swap(Origin::signed(2), 0));
+
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![2]);
+        assert_eq!(KittiesModule::owned_kitties(&2, 0, 10), vec![0, 1]);
+        assert_eq!(Balances::free_balance(1), 100000000 - 5000 * 2 - 300);
+        assert_eq!(Balances::free_balance(2), 100000000 - 5000 + 300);
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::reserved_balance(2), 10000);
+        assert_eq!(KittiesModule::swap_offer(0), None);
+        assert_eq!(
+            System::events().last().unwrap().event,
+            TestEvent::kitties_event(Event::<Test>::SwapAccepted(1u64, 2, 0)),
+        );
+    })
+}
+
+// swap fails as a whole when an item changed hands
+#[test]
+fn swap_accept_failed_when_kitty_moved() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(1), 2, vec![0], vec![1], None));
+        assert_ok!(KittiesModule::transfer(Origin::signed(2), 3, 1));
+
+        assert_noop!(KittiesModule::accept_swap(Origin::signed(2), 0), Error::<Test>::NotKittyOwner);
+        assert_eq!(KittiesModule::kitty_owner(0), Some(1));
+    })
+}
+
+// cancel swap
+#[test]
+fn swap_cancel_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(1), 2, vec![0], vec![], Some(10)));
+        assert_noop!(KittiesModule::cancel_swap(Origin::signed(2), 0), Error::<Test>::NotSwapMaker);
+        assert_ok!(KittiesModule::cancel_swap(Origin::signed(1), 0));
+        assert_noop!(KittiesModule::accept_swap(Origin::signed(2), 0), Error::<Test>::SwapNotExists);
+    })
+}
+
+// other pallets can query kitties
+#[test]
+fn nft_inspect_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::breed(Origin::signed(1), 0, 1);
+
+        assert_eq!(<KittiesModule as NftInspect<u64>>::owner_of(2), Some(1));
+        assert_eq!(
+            <KittiesModule as NftInspect<u64>>::dna_of(2),
+            KittiesModule::kitties(2).map(|kitty| kitty.get_self_dna())
+        );
+        assert_eq!(<KittiesModule as NftInspect<u64>>::dna_of(3), None);
+        assert_eq!(<KittiesModule as NftInspect<u64>>::parents_of(2), Some((0, 1)));
+        assert_eq!(<KittiesModule as NftInspect<u64>>::parents_of(0), None);
+    })
+}
+
+// children are filed under both parents
+#[test]
+fn nft_inspect_children_of_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 2, 1));
+
+        assert_eq!(<KittiesModule as NftInspect<u64>>::children_of(0, 1), vec![3, 4]);
+        assert_eq!(<KittiesModule as NftInspect<u64>>::children_of(2, 1), vec![5]);
+        assert_eq!(<KittiesModule as NftInspect<u64>>::children_of(1, 1), Vec::<u8>::new());
+        assert_eq!(KittiesModule::kitty_brother(4), vec![3]);
+        assert_eq!(KittiesModule::kitty_brother(5), Vec::<u8>::new());
+    })
+}
+
+// children filed under the wrong key are moved to both parents
+#[test]
+fn on_runtime_upgrade_rebuilds_kitty_children() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::breed(Origin::signed(1), 0, 1);
+        let _ = KittiesModule::breed(Origin::signed(1), 2, 1);
+
+        // rewind to the old layout, both children under (mother, mother)
+        <KittyChildren<Test>>::remove(0, 1);
+        <KittyChildren<Test>>::remove(2, 1);
+        <KittyChildren<Test>>::insert(1, 1, vec![3, 4]);
+        <KittyBrother<Test>>::insert(4, vec![3]);
+        StorageVersion::put(Releases::V3_0_0);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(KittiesModule::kitty_children(0, 1), vec![3]);
+        assert_eq!(KittiesModule::kitty_children(2, 1), vec![4]);
+        assert!(!<KittyChildren<Test>>::contains_key(1, 1));
+        assert_eq!(KittiesModule::kitty_brother(4), Vec::<u8>::new());
+        assert_eq!(KittiesModule::storage_version(), Releases::LATEST);
+        assert_ok!(post_migrate::<Test>());
+    })
+}
+
+// locked kitties can't change hands
+#[test]
+fn locked_kitty_can_not_be_transferred() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(<KittiesModule as NftTransfer<u64>>::lock(0));
+        assert_noop!(<KittiesModule as NftTransfer<u64>>::lock(0), Error::<Test>::KittyLocked);
+        assert_noop!(
+            KittiesModule::transfer(Origin::signed(1), 2, 0),
+            Error::<Test>::KittyLocked
+        );
+
+        assert_ok!(<KittiesModule as NftTransfer<u64>>::unlock(0));
+        assert_noop!(<KittiesModule as NftTransfer<u64>>::unlock(0), Error::<Test>::KittyNotLocked);
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+    })
+}
+
+// transfer hook is called
+#[test]
+fn on_kitty_transfer_hook_is_called() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(<KittiesModule as NftTransfer<u64>>::transfer(&1, &2, 0));
+        assert_noop!(
+            <KittiesModule as NftTransfer<u64>>::transfer(&1, &3, 0),
+            Error::<Test>::NotKittyOwner
+        );
+
+        assert_eq!(KITTY_TRANSFERS.with(|t| t.borrow().clone()), vec![(1, 2, 0)]);
+    })
+}
+
+// ids run up to the last value below KittyIndex::max_value()
+#[test]
+fn create_failed_when_kitty_ids_run_out() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        <KittiesCount<Test>>::put(253);
+
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittiesModule::kitty_owner(254), Some(1));
+        assert_eq!(KittiesModule::kitties_count(), 255);
+
+        assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::KittiesCountOverflow);
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 253, 254), Error::<Test>::KittiesCountOverflow);
+    })
+}
+
+// a failed create does not consume an id
+#[test]
+fn failed_create_keeps_kitty_id() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+
+        assert_noop!(KittiesModule::create(Origin::signed(9)), Error::<Test>::BalanceNotEnough);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittiesModule::kitty_owner(0), Some(1));
+    })
+}
+
+// burn releases the deposit and frees the id
+#[test]
+fn burn_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_noop!(KittiesModule::burn(Origin::signed(2), 0), Error::<Test>::NotKittyOwner);
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+
+        assert!(KittiesModule::kitties(0).is_none());
+        assert_eq!(KittiesModule::kitty_owner(0), None);
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![1]);
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(
+            System::events().last().unwrap().event,
+            TestEvent::kitties_event(Event::<Test>::Burned(1u64, 0)),
+        );
+    })
+}
+
+// burned ids are handed out again
+#[test]
+fn burned_kitty_id_is_reused() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        <KittiesCount<Test>>::put(254);
+        let _ = KittiesModule::create(Origin::signed(1));
+        assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::KittiesCountOverflow);
+
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 254));
+        assert_eq!(KittiesModule::free_kitty_ids_count(), 1);
+
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_eq!(KittiesModule::kitty_owner(254), Some(2));
+        assert_eq!(KittiesModule::free_kitty_ids_count(), 0);
+        assert_eq!(KittiesModule::kitties_count(), 255);
+    })
+}
+
+// ids named by open swap offers are never reused
+#[test]
+fn burned_kitty_id_in_swap_offer_is_not_reused() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(2), 1, vec![2], vec![0], None));
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(2), 1, vec![], vec![1], Some(10)));
+        assert_eq!(KittiesModule::swap_offered_kitties(0), 1);
+
+        assert_ok!(KittiesModule::cancel_swap(Origin::signed(2), 1));
+        assert_eq!(KittiesModule::swap_offered_kitties(1), 0);
+
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 1));
+        // only the id that is no longer offered goes back on the free list
+        assert_eq!(KittiesModule::free_kitty_ids_count(), 1);
+        assert_eq!(KittiesModule::free_kitty_ids(0), Some(1));
+
+        // the stale offer can't pick up a new kitty
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittiesModule::kitty_owner(0), None);
+        assert_noop!(KittiesModule::accept_swap(Origin::signed(1), 0), Error::<Test>::KittyNotExists);
+    })
+}
+
+// open offers are counted for chains that had them before the count existed
+#[test]
+fn on_runtime_upgrade_counts_swap_offers() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(1), 2, vec![0], vec![1], None));
+        assert_ok!(KittiesModule::offer_swap(Origin::signed(2), 1, vec![1], vec![], None));
+
+        // rewind to the old layout
+        <SwapOfferedKitties<Test>>::remove(0);
+        <SwapOfferedKitties<Test>>::remove(1);
+        StorageVersion::put(Releases::V4_0_0);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(KittiesModule::swap_offered_kitties(0), 1);
+        assert_eq!(KittiesModule::swap_offered_kitties(1), 2);
+        assert_eq!(KittiesModule::storage_version(), Releases::LATEST);
+    })
+}
+
+// without reuse the id stays retired
+#[test]
+fn burned_kitty_id_is_not_reused_when_disabled() {
+    new_test_ext().execute_with(|| {
+        ReuseKittyIds::set(false);
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        assert_eq!(KittiesModule::kitty_owner(0), None);
+        assert_eq!(KittiesModule::kitty_owner(1), Some(1));
+    })
+}
+
+// ids with lineage records are never reused
+#[test]
+fn bred_kitty_id_is_not_reused() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::breed(Origin::signed(1), 0, 1);
+
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 2));
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+        assert_eq!(KittiesModule::free_kitty_ids_count(), 0);
+    })
+}
+
+// locked kitties can't be burned
+#[test]
+fn locked_kitty_can_not_be_burned() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        assert_ok!(<KittiesModule as NftTransfer<u64>>::lock(0));
+
+        assert_noop!(KittiesModule::burn(Origin::signed(1), 0), Error::<Test>::KittyLocked);
+    })
+}
+
+// re-keying kitty storage keeps every entry
+#[test]
+fn widen_kitty_index_preserves_storage() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::breed(Origin::signed(1), 0, 1);
+        let _ = KittiesModule::transfer(Origin::signed(1), 2, 1);
+        let _ = KittiesModule::ask(Origin::signed(1), 2, Some(100));
+        let _ = KittiesModule::offer_swap(Origin::signed(1), 2, vec![0], vec![1], None);
+        let root = sp_io::storage::root();
+
+        assert_eq!(stored_index_width::<Test>(), Some(1));
+        // same width in and out, so the storage must come back unchanged
+        widen_kitty_index::<Test, u8>();
+
+        assert_eq!(sp_io::storage::root(), root);
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![0, 2]);
+        assert_eq!(KittiesModule::kitty_parents(2), (0, 1));
+        assert_eq!(KittiesModule::kitty_price(2), Some(100));
+    })
+}
+
+// upgrade checks pass on a migrated chain
+#[test]
+fn migration_checks_pass_after_upgrade() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::transfer(Origin::signed(1), 2, 0);
+        assert_eq!(KittiesModule::storage_version(), Releases::V1_0_0);
+
+        assert_ok!(pre_migrate::<Test>());
+        KittiesModule::on_runtime_upgrade();
+        assert_ok!(post_migrate::<Test>());
+    })
+}
+
+// deposits of existing kitties are recorded, capped by what is still reserved
+#[test]
+fn on_runtime_upgrade_attributes_no_reserves_to_old_kitties() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+
+        // rewind to the old layout, where the reserve of kitty 0 stayed with its creator
+        for kitty_id in 0..2 {
+            <KittyDeposits<Test>>::remove(kitty_id);
+        }
+        Balances::unreserve(&2, 5000);
+        assert_ok!(Balances::reserve(&1, 5000));
+        StorageVersion::put(Releases::V2_0_0);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(KittiesModule::kitty_deposit(0), None);
+        assert_eq!(KittiesModule::kitty_deposit(1), None);
+        assert_eq!(KittiesModule::storage_version(), Releases::LATEST);
+        assert_ok!(post_migrate::<Test>());
+
+        // reserves of account 2 that aren't attributed to a kitty stay where they are
+        assert_ok!(KittiesModule::transfer(Origin::signed(2), 3, 0));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::reserved_balance(2), 5000);
+        assert_eq!(Balances::reserved_balance(3), 0);
+    })
+}
+
+// post-upgrade checks catch a broken owner index
+#[test]
+fn migration_checks_fail_on_broken_index() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        KittiesModule::on_runtime_upgrade();
+
+        <OwnedKittiesIndex<Test>>::insert(0, 3);
+        assert_eq!(post_migrate::<Test>(), Err("kitty missing from its owner's index"));
+    })
+}
+
+#[test]
+fn mint_requires_mint_origin() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let dna = DNA([7; 16]);
+
+        assert_noop!(KittiesModule::mint(Origin::signed(1), 1, dna), DispatchError::BadOrigin);
+
+        assert_ok!(KittiesModule::mint(Origin::root(), 1, dna));
+        assert_eq!(KittiesModule::dna_of(0), Some(dna));
+        assert_eq!(KittiesModule::kitty_owner(0), Some(1));
+    })
+}
+
+#[test]
+fn mint_takes_no_deposit() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+
+        // even an account without funds can be given a kitty
+        assert_ok!(KittiesModule::mint(Origin::root(), 10, DNA([7; 16])));
+        assert_eq!(KittiesModule::kitty_owner(0), Some(10));
+        assert_eq!(KittiesModule::kitty_deposit(0), None);
+        assert_eq!(Balances::reserved_balance(10), 0);
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(10), 1, 0));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+        assert_eq!(Balances::free_balance(1), 100000000);
+    })
+}
+
+#[test]
+fn force_transfer_moves_kitty_and_deposit() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        assert_noop!(KittiesModule::force_transfer(Origin::signed(2), 2, 0), DispatchError::BadOrigin);
+        assert_noop!(KittiesModule::force_transfer(Origin::root(), 1, 0), Error::<Test>::TransferToSelf);
+
+        assert_ok!(KittiesModule::force_transfer(Origin::root(), 2, 0));
+        assert_eq!(KittiesModule::kitty_owner(0), Some(2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 5_000);
+    })
+}
+
+#[test]
+fn buy_pays_market_commission() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::ask(Origin::signed(1), 0, Some(10_000)));
+
+        assert_ok!(KittiesModule::buy(Origin::signed(2), 0, 10_000));
+
+        assert_eq!(KittiesModule::kitty_owner(0), Some(2));
+        // 10% of the price goes to the commission pot, the buyer pays the full price
+        assert_eq!(Balances::free_balance(1), 100000000 - 5_000 + 9_000);
+        assert_eq!(Balances::free_balance(2), 100000000 - 10_000);
+        assert_eq!(Balances::free_balance(COMMISSION_POT), 1_000);
+    })
+}
@@ -0,0 +1,61 @@
+//! Traits that let other pallets own and query kitties without touching this pallet's storage.
+
+use sp_runtime::DispatchResult;
+use sp_std::vec::Vec;
+use crate::DNA;
+
+/// Read-only access to kitties.
+pub trait NftInspect<AccountId> {
+    /// Identifier of a kitty.
+    type KittyIndex;
+
+    /// Current owner of a kitty, `None` if it does not exist.
+    fn owner_of(kitty_id: Self::KittyIndex) -> Option<AccountId>;
+
+    /// DNA of a kitty, `None` if it does not exist.
+    fn dna_of(kitty_id: Self::KittyIndex) -> Option<DNA>;
+
+    /// Parents of a bred kitty, `None` for kitties created from scratch.
+    fn parents_of(kitty_id: Self::KittyIndex) -> Option<(Self::KittyIndex, Self::KittyIndex)>;
+
+    /// Kitties bred from the given pair of parents.
+    fn children_of(father: Self::KittyIndex, mother: Self::KittyIndex) -> Vec<Self::KittyIndex>;
+
+    /// Whether a kitty is locked and can't change hands.
+    fn is_locked(kitty_id: Self::KittyIndex) -> bool;
+}
+
+/// Moving and locking kitties on behalf of their owners.
+pub trait NftTransfer<AccountId>: NftInspect<AccountId> {
+    /// Transfer a kitty owned by `from` to `to`, together with its deposit.
+    ///
+    /// Callers must be transactional, the kitty and its deposit move in several steps.
+    fn transfer(from: &AccountId, to: &AccountId, kitty_id: Self::KittyIndex) -> DispatchResult;
+
+    /// Lock a kitty so it can't be transferred, sold or swapped until unlocked.
+    fn lock(kitty_id: Self::KittyIndex) -> DispatchResult;
+
+    /// Release a kitty locked with `lock`.
+    fn unlock(kitty_id: Self::KittyIndex) -> DispatchResult;
+}
+
+/// Hook called after a kitty changed owner.
+pub trait OnKittyTransfer<AccountId, KittyIndex> {
+    /// `kitty_id` moved from `from` to `to`.
+    fn on_kitty_transfer(from: &AccountId, to: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> OnKittyTransfer<AccountId, KittyIndex> for () {
+    fn on_kitty_transfer(_: &AccountId, _: &AccountId, _: KittyIndex) {}
+}
+
+/// Hook called after a kitty was burned, before its id can be handed out again.
+pub trait OnKittyBurn<AccountId, KittyIndex> {
+    /// `kitty_id` owned by `owner` was burned. Anything kept about it must be dropped,
+    /// or the next kitty with the same id inherits it.
+    fn on_kitty_burn(owner: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> OnKittyBurn<AccountId, KittyIndex> for () {
+    fn on_kitty_burn(_: &AccountId, _: KittyIndex) {}
+}
@@ -0,0 +1,382 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Encode, Decode};
+use frame_support::{decl_module, decl_storage, decl_event, dispatch,
+                    ensure, decl_error, StorageValue, StorageMap, IterableStorageMap, Parameter, transactional,
+                    traits::{Get, Randomness, Currency, ReservableCurrency, BalanceStatus},
+                    weights::Weight,
+};
+use sp_io::hashing::blake2_128;
+use frame_system::ensure_signed;
+use sp_runtime::traits::{One, Saturating};
+use sp_std::prelude::*;
+use sp_std::vec;
+use pallet_kitties::{DNA, NftTransfer, OnKittyBurn};
+
+pub mod migrations;
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of exchanges before a fight is decided on remaining health.
+const MAX_ROUNDS: usize = 16;
+
+/// Combat stats derived from kitty DNA.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct CombatStats {
+    pub attack: u16,
+    pub defense: u16,
+    pub speed: u16,
+    pub health: u16,
+}
+
+impl CombatStats {
+    /// Each stat sums four DNA bytes, health gets a base of 500.
+    pub fn from_dna(dna: &DNA) -> Self {
+        let genes = |i: usize| (dna[i] as u16) + (dna[i + 1] as u16) + (dna[i + 2] as u16) + (dna[i + 3] as u16);
+
+        Self {
+            attack: genes(0),
+            defense: genes(4),
+            speed: genes(8),
+            health: 500 + genes(12),
+        }
+    }
+
+    fn damage_to(&self, defender: &CombatStats, luck: u8) -> u32 {
+        let base = (self.attack as u32) * 200 / (100 + defender.defense as u32);
+        base.max(1) + (luck as u32) % 32
+    }
+}
+
+/// Fight `a` against `b`. Returns `true` if `a` wins.
+///
+/// The faster kitty strikes first in every exchange, `seed` adds some luck to each strike.
+pub fn resolve_fight(a: &CombatStats, b: &CombatStats, seed: &[u8; 16]) -> bool {
+    let a_first = a.speed > b.speed || (a.speed == b.speed && seed[0] & 1 == 0);
+    let mut health_a = a.health as u32;
+    let mut health_b = b.health as u32;
+
+    for round in 0..MAX_ROUNDS {
+        let luck_a = seed[round % 16];
+        let luck_b = seed[(round + 8) % 16];
+
+        if a_first {
+            health_b = health_b.saturating_sub(a.damage_to(b, luck_a));
+            if health_b == 0 { return true; }
+            health_a = health_a.saturating_sub(b.damage_to(a, luck_b));
+            if health_a == 0 { return false; }
+        } else {
+            health_a = health_a.saturating_sub(b.damage_to(a, luck_b));
+            if health_a == 0 { return false; }
+            health_b = health_b.saturating_sub(a.damage_to(b, luck_a));
+            if health_b == 0 { return true; }
+        }
+    }
+
+    if health_a == health_b {
+        a_first
+    } else {
+        health_a > health_b
+    }
+}
+
+/// An open challenge waiting for the opponent kitty's owner.
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Challenge<AccountId, KittyIndex, Balance> {
+    /// Account that issued the challenge.
+    pub challenger: AccountId,
+    /// Kitty fighting for the challenger. Locked until the fight is resolved or cancelled.
+    pub kitty: KittyIndex,
+    /// Kitty being challenged.
+    pub opponent_kitty: KittyIndex,
+    /// Amount each side stakes. The winner takes both.
+    pub wager: Balance,
+}
+
+/// Win/loss record of a kitty.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+pub type ChallengeIndex = u32;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    type Randomness: Randomness<Self::Hash>;
+    type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+    type KittyIndex: Parameter + Copy;
+    /// Where the fighting kitties live.
+    type Kitties: NftTransfer<Self::AccountId, KittyIndex = Self::KittyIndex>;
+    /// Smallest wager a challenge can stake.
+    type MinimumWager: Get<BalanceOf<Self>>;
+    /// Number of kitties kept on the leaderboard.
+    type MaxLeaderboardSize: Get<u32>;
+    /// Blocks between accepting a challenge and the fight, at least one.
+    ///
+    /// The fight is seeded with randomness from after the accept, so the accepter can't
+    /// know the outcome when deciding to accept.
+    type FightDelay: Get<Self::BlockNumber>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as KittyArena {
+        // challenge id => open challenge
+        pub Challenges get(fn challenges): map hasher(twox_64_concat) ChallengeIndex => Option<Challenge<T::AccountId, T::KittyIndex, BalanceOf<T>>>;
+        // challenge count
+        pub ChallengesCount get(fn challenges_count): ChallengeIndex;
+        // accepted challenge id => (opponent, block the fight takes place)
+        pub Fights get(fn fight): map hasher(twox_64_concat) ChallengeIndex => Option<(T::AccountId, T::BlockNumber)>;
+        // block => challenges fought at the start of it
+        pub FightsAt get(fn fights_at): map hasher(twox_64_concat) T::BlockNumber => vec::Vec<ChallengeIndex>;
+        // kitty id => win/loss record
+        pub KittyRecords get(fn kitty_record): map hasher(blake2_128_concat) T::KittyIndex => Record;
+        // kitties with the most wins, best first
+        pub Leaderboard get(fn leaderboard): vec::Vec<(T::KittyIndex, u32)>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T> where
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::BlockNumber,
+    <T as Trait>::KittyIndex,
+    {
+        /// A challenge is issued. (challenger, opponent, challenge_id)
+        ChallengeIssued(AccountId, AccountId, ChallengeIndex),
+        /// A challenge is accepted, the fight takes place at the given block. (challenge_id, fight_at)
+        ChallengeAccepted(ChallengeIndex, BlockNumber),
+        /// A challenge is cancelled. (challenge_id)
+        ChallengeCancelled(ChallengeIndex),
+        /// A fight is resolved. (challenge_id, winner, winner_kitty, loser_kitty)
+        FightResolved(ChallengeIndex, AccountId, KittyIndex, KittyIndex),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        KittyNotExists,
+        NotKittyOwner,
+        ChallengeSelf,
+        WagerTooLow,
+        BalanceNotEnough,
+        ChallengeNotExists,
+        NotChallengeParticipant,
+        ChallengesCountOverflow,
+        ChallengeAccepted,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Errors must be initialized if they are used by the pallet.
+        type Error = Error<T>;
+
+        // Events must be initialized if they are used by the pallet.
+        fn deposit_event() = default;
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let fights = <FightsAt<T>>::take(now);
+            for &challenge_id in fights.iter() {
+                Self::resolve(challenge_id);
+            }
+
+            let fights = fights.len() as Weight;
+            T::DbWeight::get().reads_writes(1 + fights * 8, 1 + fights * 10)
+        }
+
+        /// Challenge the owner of `opponent_kitty_id`, staking `wager`. `kitty_id` is locked meanwhile.
+        #[weight = 0]
+        #[transactional]
+        pub fn challenge(
+            origin,
+            kitty_id: T::KittyIndex,
+            opponent_kitty_id: T::KittyIndex,
+            wager: BalanceOf<T>,
+        ) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(wager >= T::MinimumWager::get(), Error::<T>::WagerTooLow);
+
+            let owner = T::Kitties::owner_of(kitty_id).ok_or(Error::<T>::KittyNotExists)?;
+            ensure!(owner == sender, Error::<T>::NotKittyOwner);
+            let opponent = T::Kitties::owner_of(opponent_kitty_id).ok_or(Error::<T>::KittyNotExists)?;
+            ensure!(opponent != sender, Error::<T>::ChallengeSelf);
+
+            let challenge_id = Self::challenges_count();
+            let next_challenge_id = challenge_id.checked_add(1).ok_or(Error::<T>::ChallengesCountOverflow)?;
+
+            T::Kitties::lock(kitty_id)?;
+            T::Currency::reserve(&sender, wager).map_err(|_| Error::<T>::BalanceNotEnough)?;
+
+            <Challenges<T>>::insert(challenge_id, Challenge {
+                challenger: sender.clone(),
+                kitty: kitty_id,
+                opponent_kitty: opponent_kitty_id,
+                wager,
+            });
+            ChallengesCount::put(next_challenge_id);
+
+            Self::deposit_event(RawEvent::ChallengeIssued(sender, opponent, challenge_id));
+
+            Ok(())
+        }
+
+        /// Accept a challenge against one of your kitties, staking the same wager.
+        ///
+        /// Both kitties stay locked until the fight at the start of block `now + FightDelay`.
+        #[weight = 0]
+        #[transactional]
+        pub fn accept(origin, challenge_id: ChallengeIndex) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::ChallengeNotExists)?;
+            ensure!(!<Fights<T>>::contains_key(challenge_id), Error::<T>::ChallengeAccepted);
+            let owner = T::Kitties::owner_of(challenge.opponent_kitty).ok_or(Error::<T>::KittyNotExists)?;
+            ensure!(owner == sender, Error::<T>::NotKittyOwner);
+            ensure!(challenge.challenger != sender, Error::<T>::ChallengeSelf);
+
+            T::Kitties::lock(challenge.opponent_kitty)?;
+            T::Currency::reserve(&sender, challenge.wager).map_err(|_| Error::<T>::BalanceNotEnough)?;
+
+            let delay = T::FightDelay::get().max(One::one());
+            let fight_at = <frame_system::Module<T>>::block_number().saturating_add(delay);
+            <Fights<T>>::insert(challenge_id, (sender, fight_at));
+            <FightsAt<T>>::mutate(fight_at, |fights| fights.push(challenge_id));
+
+            Self::deposit_event(RawEvent::ChallengeAccepted(challenge_id, fight_at));
+
+            Ok(())
+        }
+
+        /// Withdraw a challenge that has not been accepted yet.
+        /// Either the challenger or the opponent kitty's owner may cancel.
+        #[weight = 0]
+        pub fn cancel(origin, challenge_id: ChallengeIndex) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::ChallengeNotExists)?;
+            ensure!(
+                challenge.challenger == sender || T::Kitties::owner_of(challenge.opponent_kitty) == Some(sender),
+                Error::<T>::NotChallengeParticipant
+            );
+            ensure!(!<Fights<T>>::contains_key(challenge_id), Error::<T>::ChallengeAccepted);
+
+            // unlock first, nothing is written if it fails
+            T::Kitties::unlock(challenge.kitty)?;
+            T::Currency::unreserve(&challenge.challenger, challenge.wager);
+            <Challenges<T>>::remove(challenge_id);
+
+            Self::deposit_event(RawEvent::ChallengeCancelled(challenge_id));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Fight an accepted challenge. The winner gets their own stake back plus the loser's.
+    fn resolve(challenge_id: ChallengeIndex) {
+        let (challenge, opponent) = match (<Challenges<T>>::take(challenge_id), <Fights<T>>::take(challenge_id)) {
+            (Some(challenge), Some((opponent, _))) => (challenge, opponent),
+            _ => return,
+        };
+
+        let _ = T::Kitties::unlock(challenge.kitty);
+        let _ = T::Kitties::unlock(challenge.opponent_kitty);
+
+        let (dna, opponent_dna) = match (T::Kitties::dna_of(challenge.kitty), T::Kitties::dna_of(challenge.opponent_kitty)) {
+            (Some(dna), Some(opponent_dna)) => (dna, opponent_dna),
+            // locked kitties can't be burned, but never keep the stakes if one is gone
+            _ => {
+                T::Currency::unreserve(&challenge.challenger, challenge.wager);
+                T::Currency::unreserve(&opponent, challenge.wager);
+                Self::deposit_event(RawEvent::ChallengeCancelled(challenge_id));
+                return;
+            }
+        };
+
+        let seed = Self::fight_seed(challenge_id, &dna, &opponent_dna);
+        let challenger_wins = resolve_fight(
+            &CombatStats::from_dna(&dna),
+            &CombatStats::from_dna(&opponent_dna),
+            &seed,
+        );
+
+        let (winner, loser, winner_kitty, loser_kitty) = if challenger_wins {
+            (challenge.challenger, opponent, challenge.kitty, challenge.opponent_kitty)
+        } else {
+            (opponent, challenge.challenger, challenge.opponent_kitty, challenge.kitty)
+        };
+
+        T::Currency::unreserve(&winner, challenge.wager);
+        // the winner holds a reserve so exists, a slashed stake pays out what is left of it
+        let _ = T::Currency::repatriate_reserved(&loser, &winner, challenge.wager, BalanceStatus::Free);
+
+        Self::record_fight(winner_kitty, loser_kitty);
+
+        Self::deposit_event(RawEvent::FightResolved(challenge_id, winner, winner_kitty, loser_kitty));
+    }
+
+    fn fight_seed(challenge_id: ChallengeIndex, dna: &DNA, opponent_dna: &DNA) -> [u8; 16] {
+        let payload = (
+            T::Randomness::random(&(&b"kitty-arena"[..], challenge_id).encode()),
+            challenge_id,
+            dna,
+            opponent_dna,
+        );
+        payload.using_encoded(blake2_128)
+    }
+
+    fn record_fight(winner_kitty: T::KittyIndex, loser_kitty: T::KittyIndex) {
+        let wins = <KittyRecords<T>>::mutate(winner_kitty, |record| {
+            record.wins = record.wins.saturating_add(1);
+            record.wins
+        });
+        <KittyRecords<T>>::mutate(loser_kitty, |record| record.losses = record.losses.saturating_add(1));
+
+        <Leaderboard<T>>::mutate(|board| {
+            board.retain(|(kitty_id, _)| *kitty_id != winner_kitty);
+            // ties keep the kitty that got there first ahead
+            let position = board.iter().position(|(_, other_wins)| *other_wins < wins).unwrap_or(board.len());
+            board.insert(position, (winner_kitty, wins));
+            board.truncate(T::MaxLeaderboardSize::get() as usize);
+        });
+    }
+}
+
+/// A burned kitty's record and leaderboard spot go with it, its id may be reused.
+/// Open challenges naming it are cancelled and refunded, so a reused id never inherits one.
+impl<T: Trait> OnKittyBurn<T::AccountId, T::KittyIndex> for Module<T> {
+    fn on_kitty_burn(_: &T::AccountId, kitty_id: T::KittyIndex) {
+        <KittyRecords<T>>::remove(kitty_id);
+        <Leaderboard<T>>::mutate(|board| board.retain(|(other, _)| *other != kitty_id));
+
+        // accepted challenges lock both kitties, so only open ones can name a burned kitty
+        let open: Vec<(ChallengeIndex, Challenge<T::AccountId, T::KittyIndex, BalanceOf<T>>)> = <Challenges<T>>::iter()
+            .filter(|(challenge_id, challenge)| {
+                (challenge.kitty == kitty_id || challenge.opponent_kitty == kitty_id)
+                    && !<Fights<T>>::contains_key(challenge_id)
+            })
+            .collect();
+        for (challenge_id, challenge) in open {
+            if challenge.kitty != kitty_id {
+                let _ = T::Kitties::unlock(challenge.kitty);
+            }
+            T::Currency::unreserve(&challenge.challenger, challenge.wager);
+            <Challenges<T>>::remove(challenge_id);
+            Self::deposit_event(RawEvent::ChallengeCancelled(challenge_id));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Storage migrations for the kitty arena pallet.
+
+use codec::Decode;
+use frame_support::{
+    StorageValue, StorageMap,
+    storage::migration::{StorageIterator, take_storage_value},
+    traits::Get,
+    weights::Weight,
+};
+use sp_std::prelude::*;
+use crate::*;
+
+const MODULE: &[u8] = b"KittyArena";
+/// Length of the hash in front of a `blake2_128_concat` key.
+const BLAKE2_128_LEN: usize = 16;
+/// Length of the hash in front of a `twox_64_concat` key.
+const TWOX_64_LEN: usize = 8;
+
+/// Re-encode every kitty index in storage after `T::KittyIndex` was widened from `OldIndex`.
+///
+/// Run it together with `pallet_kitties::migrations::widen_kitty_index`, under the same guard.
+pub fn widen_kitty_index<T, OldIndex>() -> Weight where
+    T: Trait,
+    OldIndex: Decode + Into<T::KittyIndex>,
+{
+    let mut entries: Weight = 0;
+
+    // collect first so re-inserted keys are not visited again with the old value type
+    let challenges: Vec<(Vec<u8>, Challenge<T::AccountId, OldIndex, BalanceOf<T>>)> =
+        StorageIterator::new(MODULE, b"Challenges").drain().collect();
+    for (key, challenge) in challenges {
+        if let Some(challenge_id) = key.get(TWOX_64_LEN..).and_then(|mut raw| ChallengeIndex::decode(&mut raw).ok()) {
+            <Challenges<T>>::insert(challenge_id, Challenge {
+                challenger: challenge.challenger,
+                kitty: challenge.kitty.into(),
+                opponent_kitty: challenge.opponent_kitty.into(),
+                wager: challenge.wager,
+            });
+            entries += 1;
+        }
+    }
+
+    let records: Vec<(Vec<u8>, Record)> = StorageIterator::new(MODULE, b"KittyRecords").drain().collect();
+    for (key, record) in records {
+        if let Some(kitty_id) = key.get(BLAKE2_128_LEN..).and_then(|mut raw| OldIndex::decode(&mut raw).ok()) {
+            <KittyRecords<T>>::insert(kitty_id.into(), record);
+            entries += 1;
+        }
+    }
+
+    if let Some(board) = take_storage_value::<Vec<(OldIndex, u32)>>(MODULE, b"Leaderboard", &[]) {
+        <Leaderboard<T>>::put(board.into_iter().map(|(kitty_id, wins)| (kitty_id.into(), wins)).collect::<Vec<_>>());
+        entries += 1;
+    }
+
+    T::DbWeight::get().reads_writes(entries, entries * 2)
+}
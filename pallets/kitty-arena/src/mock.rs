@@ -0,0 +1,150 @@
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_event, impl_outer_origin, parameter_types,
+    traits::{OnFinalize, OnInitialize},
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill, Permill,
+};
+
+use balances;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+mod arena_event {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_kitties<T>,
+        arena_event<T>,
+        balances<T>,
+    }
+}
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for Test {
+    type Balance = u64;
+    type MaxLocks = ();
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<Test>;
+    type WeightInfo = ();
+}
+
+type Randomness = pallet_randomness_collective_flip::Module<Test>;
+
+parameter_types! {
+    pub const NewKittyReserve: u64 = 5_000;
+    pub const MaxBatchSize: u32 = 3;
+    pub const ReuseKittyIds: bool = true;
+    pub const MarketCommission: Permill = Permill::zero();
+}
+
+impl pallet_kitties::Trait for Test {
+    type Event = TestEvent;
+    // same as the runtime, so narrower indexes can be migrated
+    type KittyIndex = u64;
+    type Randomness = Randomness;
+    type NewKittyReserve = NewKittyReserve;
+    type Currency = balances::Module<Self>;
+    type MaxBatchSize = MaxBatchSize;
+    type OnKittyTransfer = ();
+    type OnKittyBurn = KittyArena;
+    type ReuseKittyIds = ReuseKittyIds;
+    type MintOrigin = system::EnsureRoot<u64>;
+    type ForceOrigin = system::EnsureRoot<u64>;
+    type MarketCommission = MarketCommission;
+    type OnCommission = ();
+}
+
+parameter_types! {
+    pub const MinimumWager: u64 = 100;
+    pub const MaxLeaderboardSize: u32 = 2;
+    pub const FightDelay: u64 = 2;
+}
+
+impl Trait for Test {
+    type Event = TestEvent;
+    type Randomness = Randomness;
+    type Currency = balances::Module<Self>;
+    type KittyIndex = u64;
+    type Kitties = KittiesModule;
+    type MinimumWager = MinimumWager;
+    type MaxLeaderboardSize = MaxLeaderboardSize;
+    type FightDelay = FightDelay;
+}
+
+pub type KittyArena = Module<Test>;
+pub type KittiesModule = pallet_kitties::Module<Test>;
+pub type Balances = balances::Module<Test>;
+pub type System = frame_system::Module<Test>;
+
+/// Run until a particular block.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		if System::block_number() > 1 {
+			KittyArena::on_finalize(System::block_number());
+			System::on_finalize(System::block_number());
+		}
+		System::set_block_number(System::block_number() + 1);
+		System::on_initialize(System::block_number());
+		KittyArena::on_initialize(System::block_number());
+	}
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100000000), (2, 100000000), (3, 100000000)],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
+}
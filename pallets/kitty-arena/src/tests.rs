@@ -0,0 +1,365 @@
+use crate::{mock::*, Error, Event, Challenge, CombatStats, Record, resolve_fight};
+use crate::migrations::widen_kitty_index;
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, Blake2_128Concat, Twox64Concat, StorageHasher, StorageMap, StorageValue};
+use frame_support::storage::migration::put_storage_value;
+use pallet_kitties::{DNA, Kitty, NftInspect, NftTransfer, Releases, SwapOffer};
+use pallet_kitties::migrations::{stored_index_width, post_migrate};
+
+fn stats(attack: u16, defense: u16, speed: u16, health: u16) -> CombatStats {
+    CombatStats { attack, defense, speed, health }
+}
+
+// stats come from DNA bytes
+#[test]
+fn combat_stats_from_dna() {
+    let dna = DNA::new().set([1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4]);
+    assert_eq!(CombatStats::from_dna(&dna), stats(4, 8, 12, 516));
+
+    let dna = DNA::new().set([255; 16]);
+    assert_eq!(CombatStats::from_dna(&dna), stats(1020, 1020, 1020, 1520));
+}
+
+// fights are deterministic for a given seed
+#[test]
+fn resolve_fight_is_deterministic() {
+    let strong = stats(1000, 800, 500, 1500);
+    let weak = stats(10, 10, 10, 510);
+
+    assert!(resolve_fight(&strong, &weak, &[0; 16]));
+    assert!(!resolve_fight(&weak, &strong, &[0; 16]));
+
+    let a = stats(300, 300, 300, 900);
+    let b = stats(320, 280, 290, 880);
+    let seed = [7u8; 16];
+    assert_eq!(resolve_fight(&a, &b, &seed), resolve_fight(&a, &b, &seed));
+    // one of them wins
+    assert_ne!(resolve_fight(&a, &b, &seed), resolve_fight(&b, &a, &seed));
+}
+
+// challenge locks the kitty and stakes the wager
+#[test]
+fn challenge_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+
+        assert!(<KittiesModule as NftInspect<u64>>::is_locked(0));
+        assert_eq!(Balances::reserved_balance(1), 5000 + 1000);
+        assert_eq!(
+            System::events().last().unwrap().event,
+            TestEvent::arena_event(Event::<Test>::ChallengeIssued(1u64, 2, 0)),
+        );
+        assert_noop!(
+            KittiesModule::transfer(Origin::signed(1), 3, 0),
+            pallet_kitties::Error::<Test>::KittyLocked
+        );
+    })
+}
+
+// test challenge errors
+#[test]
+fn challenge_failed() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+
+        assert_noop!(KittyArena::challenge(Origin::signed(1), 0, 2, 10), Error::<Test>::WagerTooLow);
+        assert_noop!(KittyArena::challenge(Origin::signed(2), 0, 2, 100), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittyArena::challenge(Origin::signed(1), 0, 1, 100), Error::<Test>::ChallengeSelf);
+        assert_noop!(KittyArena::challenge(Origin::signed(1), 0, 9, 100), Error::<Test>::KittyNotExists);
+        assert_noop!(
+            KittyArena::challenge(Origin::signed(1), 0, 2, 200000000),
+            Error::<Test>::BalanceNotEnough
+        );
+    })
+}
+
+// accepting stakes the wager and schedules the fight
+#[test]
+fn accept_schedules_fight() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+
+        assert_noop!(KittyArena::accept(Origin::signed(3), 0), Error::<Test>::NotKittyOwner);
+        assert_ok!(KittyArena::accept(Origin::signed(2), 0));
+
+        assert_eq!(KittyArena::fight(0), Some((2, 7)));
+        assert_eq!(KittyArena::fights_at(7), vec![0]);
+        assert_eq!(
+            System::events().last().unwrap().event,
+            TestEvent::arena_event(Event::<Test>::ChallengeAccepted(0, 7)),
+        );
+        assert_eq!(Balances::reserved_balance(2), 5000 + 1000);
+        // neither kitty can leave before the fight, and the challenge can't be withdrawn
+        assert!(<KittiesModule as NftInspect<u64>>::is_locked(1));
+        assert_noop!(
+            KittiesModule::transfer(Origin::signed(2), 3, 1),
+            pallet_kitties::Error::<Test>::KittyLocked
+        );
+        assert_noop!(KittyArena::accept(Origin::signed(2), 0), Error::<Test>::ChallengeAccepted);
+        assert_noop!(KittyArena::cancel(Origin::signed(1), 0), Error::<Test>::ChallengeAccepted);
+
+        // nothing happens before the fight block
+        run_to_block(6);
+        assert_eq!(KittyArena::fight(0), Some((2, 7)));
+    })
+}
+
+// the winner takes both wagers and the records are kept
+#[test]
+fn fight_is_resolved_after_delay() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+        assert_ok!(KittyArena::accept(Origin::signed(2), 0));
+
+        run_to_block(7);
+
+        let (winner, loser, winner_kitty, loser_kitty) = match System::events().last().unwrap().event {
+            TestEvent::arena_event(Event::<Test>::FightResolved(0, winner, winner_kitty, loser_kitty)) => {
+                (winner, if winner == 1 { 2 } else { 1 }, winner_kitty, loser_kitty)
+            },
+            _ => panic!("fight not resolved"),
+        };
+
+        assert_eq!(Balances::free_balance(winner), 100000000 - 5000 + 1000);
+        assert_eq!(Balances::free_balance(loser), 100000000 - 5000 - 1000);
+        assert_eq!(Balances::reserved_balance(winner), 5000);
+        assert_eq!(Balances::reserved_balance(loser), 5000);
+        assert!(!<KittiesModule as NftInspect<u64>>::is_locked(0));
+        assert!(!<KittiesModule as NftInspect<u64>>::is_locked(1));
+        assert_eq!(KittyArena::kitty_record(winner_kitty), Record { wins: 1, losses: 0 });
+        assert_eq!(KittyArena::kitty_record(loser_kitty), Record { wins: 0, losses: 1 });
+        assert_eq!(KittyArena::leaderboard(), vec![(winner_kitty, 1)]);
+        assert_eq!(KittyArena::challenges(0), None);
+        assert_eq!(KittyArena::fight(0), None);
+        assert_eq!(KittyArena::fights_at(7), Vec::<u32>::new());
+    })
+}
+
+// leaderboard stays sorted and bounded
+#[test]
+fn leaderboard_is_bounded() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        for owner in 1..=3 {
+            let _ = KittiesModule::create(Origin::signed(owner));
+        }
+
+        let mut fights = 0;
+        for &(challenger, kitty, opponent, opponent_kitty) in &[(1, 0, 2, 1), (2, 1, 3, 2), (3, 2, 1, 0)] {
+            assert_ok!(KittyArena::challenge(Origin::signed(challenger), kitty, opponent_kitty, 100));
+            assert_ok!(KittyArena::accept(Origin::signed(opponent), fights));
+            run_to_block(System::block_number() + 2);
+            fights += 1;
+        }
+
+        let board = KittyArena::leaderboard();
+        assert!(board.len() <= 2);
+        assert!(board.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        let total_wins: u32 = (0..3).map(|kitty| KittyArena::kitty_record(kitty).wins).sum();
+        assert_eq!(total_wins, 3);
+    })
+}
+
+// cancel releases the kitty and the wager
+#[test]
+fn cancel_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+
+        assert_noop!(KittyArena::cancel(Origin::signed(3), 0), Error::<Test>::NotChallengeParticipant);
+        assert_ok!(KittyArena::cancel(Origin::signed(2), 0));
+
+        assert!(!<KittiesModule as NftInspect<u64>>::is_locked(0));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_noop!(KittyArena::accept(Origin::signed(2), 0), Error::<Test>::ChallengeNotExists);
+    })
+}
+
+// a failing cancel keeps the wager reserved
+#[test]
+fn cancel_failed_keeps_wager() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+        assert_ok!(<KittiesModule as NftTransfer<u64>>::unlock(0));
+
+        assert_noop!(KittyArena::cancel(Origin::signed(1), 0), pallet_kitties::Error::<Test>::KittyNotLocked);
+        assert_eq!(Balances::reserved_balance(1), 5000 + 1000);
+    })
+}
+
+// a burned kitty's record does not pass to the next kitty with its id
+#[test]
+fn burn_clears_record() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+        assert_ok!(KittyArena::accept(Origin::signed(2), 0));
+        run_to_block(7);
+        assert_eq!(KittyArena::leaderboard().len(), 1);
+
+        assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+        assert_ok!(KittiesModule::burn(Origin::signed(2), 1));
+        assert_eq!(KittyArena::kitty_record(0), Record::default());
+        assert_eq!(KittyArena::kitty_record(1), Record::default());
+        assert_eq!(KittyArena::leaderboard(), vec![]);
+
+        // the ids come back without a history
+        let _ = KittiesModule::create(Origin::signed(3));
+        let _ = KittiesModule::create(Origin::signed(3));
+        assert_eq!(KittiesModule::kitty_owner(0), Some(3));
+        assert_eq!(KittyArena::kitty_record(0), Record::default());
+    })
+}
+
+// burning the opponent kitty cancels and refunds the open challenge against it
+#[test]
+fn burn_cancels_open_challenges() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        let _ = KittiesModule::create(Origin::signed(1));
+        let _ = KittiesModule::create(Origin::signed(2));
+        let _ = KittiesModule::create(Origin::signed(2));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 1, 1000));
+        assert_ok!(KittyArena::challenge(Origin::signed(1), 0, 2, 1000));
+        assert_ok!(KittyArena::accept(Origin::signed(2), 1));
+
+        assert_ok!(KittiesModule::burn(Origin::signed(2), 1));
+
+        assert_eq!(KittyArena::challenges(0), None);
+        assert_eq!(Balances::reserved_balance(1), 5000 + 1000);
+        assert!(<KittiesModule as NftInspect<u64>>::is_locked(0));
+        assert!(System::events().iter().any(|record| {
+            record.event == TestEvent::arena_event(Event::<Test>::ChallengeCancelled(0))
+        }));
+
+        // the reused id has no challenge against it
+        let _ = KittiesModule::create(Origin::signed(3));
+        assert_eq!(KittiesModule::kitty_owner(1), Some(3));
+        assert_noop!(KittyArena::accept(Origin::signed(3), 0), Error::<Test>::ChallengeNotExists);
+
+        // the accepted fight still happens
+        run_to_block(7);
+        assert!(!<KittiesModule as NftInspect<u64>>::is_locked(0));
+        assert_eq!(Balances::reserved_balance(1), 5000);
+        assert_eq!(Balances::reserved_balance(2), 5000);
+    })
+}
+
+fn blake2(key: impl Encode) -> Vec<u8> {
+    Blake2_128Concat::hash(&key.encode())
+}
+
+fn twox(key: impl Encode) -> Vec<u8> {
+    Twox64Concat::hash(&key.encode())
+}
+
+fn kitties(item: &[u8], key: Vec<u8>, value: impl Encode) {
+    put_storage_value(b"Kitties", item, &key, value);
+}
+
+// kitty and arena storage written with u32 ids decodes as u64 after widening
+#[test]
+fn widen_kitty_index_from_u32() {
+    new_test_ext().execute_with(|| {
+        let dna = DNA([1; 16]);
+
+        // two kitties of account 1 bred kitty 2 of account 2, kitty 3 was burned
+        kitties(b"KittiesCount", vec![], 4u32);
+        kitties(b"FreeKittyIdsCount", vec![], 1u32);
+        kitties(b"FreeKittyIds", twox(0u32), 3u32);
+        for &(kitty_id, owner, position) in &[(0u32, 1u64, 0u32), (1, 1, 1), (2, 2, 0)] {
+            kitties(b"Kitties", blake2(kitty_id), Kitty::new().set_self_dna(dna));
+            kitties(b"KittyOwners", blake2(kitty_id), owner);
+            kitties(b"OwnedKittiesArray", [blake2(owner), twox(position)].concat(), kitty_id);
+            kitties(b"OwnedKittiesIndex", blake2(kitty_id), position);
+            kitties(b"KittyDeposits", blake2(kitty_id), (owner, 5000u64));
+        }
+        kitties(b"OwnedKittiesCount", blake2(1u64), 2u32);
+        kitties(b"OwnedKittiesCount", blake2(2u64), 1u32);
+        kitties(b"KittyParents", blake2(2u32), (0u32, 1u32));
+        kitties(b"KittyChildren", [blake2(0u32), blake2(1u32)].concat(), vec![2u32]);
+        kitties(b"KittyBrother", blake2(2u32), Vec::<u32>::new());
+        kitties(b"KittyPartner", blake2(0u32), 1u32);
+        kitties(b"KittyPartner", blake2(1u32), 0u32);
+        kitties(b"LockedKitties", blake2(0u32), true);
+        kitties(b"KittyPrices", blake2(1u32), 500u64);
+        kitties(b"SwapOffers", twox(0u32), SwapOffer::<u64, u32, u64> {
+            maker: 1,
+            taker: 2,
+            offered: vec![1],
+            wanted: vec![2],
+            balance: None,
+        });
+        kitties(b"SwapOfferedKitties", blake2(1u32), 1u32);
+        kitties(b"SwapOfferedKitties", blake2(2u32), 1u32);
+        pallet_kitties::StorageVersion::put(Releases::LATEST);
+
+        put_storage_value(b"KittyArena", b"Challenges", &twox(0u32), Challenge::<u64, u32, u64> {
+            challenger: 1,
+            kitty: 0,
+            opponent_kitty: 2,
+            wager: 100,
+        });
+        put_storage_value(b"KittyArena", b"KittyRecords", &blake2(1u32), Record { wins: 1, losses: 0 });
+        put_storage_value(b"KittyArena", b"Leaderboard", &[], vec![(1u32, 1u32)]);
+
+        assert_eq!(stored_index_width::<Test>(), Some(4));
+        pallet_kitties::migrations::widen_kitty_index::<Test, u32>();
+        widen_kitty_index::<Test, u32>();
+        assert_eq!(stored_index_width::<Test>(), Some(8));
+
+        assert_eq!(KittiesModule::kitties_count(), 4u64);
+        assert_eq!(KittiesModule::free_kitty_ids_count(), 1);
+        assert_eq!(KittiesModule::free_kitty_ids(0), Some(3));
+        assert_eq!(KittiesModule::dna_of(2), Some(dna));
+        assert_eq!(KittiesModule::kitty_owner(2), Some(2));
+        assert_eq!(KittiesModule::owned_kitties(&1, 0, 10), vec![0, 1]);
+        assert_eq!(KittiesModule::owned_kitties(&2, 0, 10), vec![2]);
+        assert_eq!(KittiesModule::kitty_parents(2), (0, 1));
+        assert_eq!(KittiesModule::kitty_children(0, 1), vec![2]);
+        assert_eq!(KittiesModule::kitty_partner(1), 0);
+        assert!(KittiesModule::kitty_locked(0));
+        assert_eq!(KittiesModule::kitty_price(1), Some(500));
+        assert_eq!(KittiesModule::kitty_deposit(2), Some((2, 5000)));
+        assert_eq!(KittiesModule::swap_offer(0).map(|offer| (offer.offered, offer.wanted)), Some((vec![1], vec![2])));
+        assert_eq!(KittiesModule::swap_offered_kitties(2), 1);
+        // every entry decodes and the owner index still matches
+        assert_ok!(post_migrate::<Test>());
+
+        assert_eq!(KittyArena::challenges(0).map(|challenge| (challenge.kitty, challenge.opponent_kitty)), Some((0, 2)));
+        assert_eq!(KittyArena::kitty_record(1), Record { wins: 1, losses: 0 });
+        assert_eq!(KittyArena::leaderboard(), vec![(1, 1)]);
+    })
+}
+
+// the verifier computes the keys the kitties pallet stores owners under
+#[test]
+fn verifier_kitty_owner_key_matches_storage() {
+    for kitty_id in vec![0, 1, 255, 256, u64::max_value()] {
+        assert_eq!(
+            node_template_proof_verifier::kitty_owner_key(kitty_id),
+            pallet_kitties::KittyOwners::<Test>::hashed_key_for(kitty_id)
+        );
+    }
+}
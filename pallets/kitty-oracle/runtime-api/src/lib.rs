@@ -0,0 +1,21 @@
+//! Runtime API of the kitty floor price oracle.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+pub use pallet_kitty_oracle::{FloorPrice, RarityTier};
+
+sp_api::decl_runtime_apis! {
+    /// Floor prices of kitties per rarity tier, as last reported by the oracles.
+    pub trait KittyOracleApi<Balance, BlockNumber> where
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        /// Floor price of `tier`, `None` if no sale of the tier was reported yet.
+        fn floor_price(tier: RarityTier) -> Option<FloorPrice<Balance, BlockNumber>>;
+
+        /// Floor prices of all tiers reported so far, most common tier first.
+        fn floor_prices() -> Vec<(RarityTier, FloorPrice<Balance, BlockNumber>)>;
+    }
+}
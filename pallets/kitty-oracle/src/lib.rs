@@ -0,0 +1,359 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Encode, Decode};
+use frame_support::{decl_module, decl_storage, decl_event, decl_error, debug,
+                    ensure, StorageValue, StorageMap, Parameter, RuntimeDebug,
+                    dispatch::{DispatchResult, DispatchResultWithPostInfo},
+                    traits::{Get, Currency, EnsureOrigin},
+                    weights::Pays,
+};
+use frame_system::{
+    ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+    RuntimeAppPublic,
+    offchain::storage::StorageValueRef,
+    traits::{Convert, IdentifyAccount, One, Saturating},
+};
+use sp_std::prelude::*;
+use sp_std::vec;
+use pallet_kitties::{DNA, NftInspect};
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Key type of the keys oracles sign price reports with.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"orcl");
+
+/// Offchain storage key of the sales the offchain worker saw in the sale window.
+const SALES_KEY: &[u8] = b"kitty-oracle::sales";
+/// Offchain storage key of the block the offchain worker last sent a report at.
+const REPORTED_KEY: &[u8] = b"kitty-oracle::reported";
+
+/// Oracle keys, sr25519 keys of type `orcl` in the node's keystore.
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_runtime::{app_crypto::{app_crypto, sr25519}, MultiSignature, MultiSigner};
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Signs reports for runtimes whose accounts are `MultiSigner`s.
+    pub struct AuthorityId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AuthorityId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
+
+/// Rarity of a kitty, decided by the first byte of its DNA.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum RarityTier {
+    /// First byte below 160, 5 in 8 kitties.
+    Common,
+    /// First byte below 224, 1 in 4 kitties.
+    Uncommon,
+    /// First byte below 252, 7 in 64 kitties.
+    Rare,
+    /// The remaining 1 in 64 kitties.
+    Legendary,
+}
+
+impl RarityTier {
+    /// All tiers, most common first.
+    pub const ALL: [RarityTier; 4] = [RarityTier::Common, RarityTier::Uncommon, RarityTier::Rare, RarityTier::Legendary];
+
+    /// Tier of a kitty with `dna`.
+    pub fn of(dna: &DNA) -> Self {
+        match dna[0] {
+            0..=159 => RarityTier::Common,
+            160..=223 => RarityTier::Uncommon,
+            224..=251 => RarityTier::Rare,
+            _ => RarityTier::Legendary,
+        }
+    }
+}
+
+/// Median price of the recent sales of a tier, across the reports of a round.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct FloorPrice<Balance, BlockNumber> {
+    pub price: Balance,
+    /// Median number of sales in the window the reported prices are of.
+    pub sales: u32,
+    /// Block the price was last updated at.
+    pub updated_at: BlockNumber,
+}
+
+/// Median of `prices`, the mean of the middle two for an even number of them.
+pub fn median<Balance>(prices: &mut [Balance]) -> Option<Balance> where
+    Balance: Ord + Copy + Saturating + sp_std::ops::Div<Output = Balance> + From<u8>,
+{
+    prices.sort();
+
+    let middle = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(prices[middle]),
+        _ => {
+            let (low, high) = (prices[middle - 1], prices[middle]);
+            Some(low.saturating_add(high.saturating_sub(low) / 2u8.into()))
+        },
+    }
+}
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// A sale the offchain worker saw: (block, tier, price).
+type Sale<T> = (<T as frame_system::Trait>::BlockNumber, RarityTier, BalanceOf<T>);
+
+/// A price report: (tier, price, sales).
+type Report<T> = vec::Vec<(RarityTier, BalanceOf<T>, u32)>;
+
+pub trait Trait: CreateSignedTransaction<Call<Self>> {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Key the offchain worker signs reports with.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+    type Currency: Currency<Self::AccountId>;
+    type KittyIndex: Parameter + Copy;
+    /// Where the sold kitties live.
+    type Kitties: NftInspect<Self::AccountId, KittyIndex = Self::KittyIndex>;
+    /// Picks kitty sales, `(kitty_id, price)`, out of the runtime's events.
+    type KittySales: Convert<<Self as frame_system::Trait>::Event, Option<(Self::KittyIndex, BalanceOf<Self>)>>;
+    /// Origin allowed to change the oracles.
+    type ForceOrigin: EnsureOrigin<Self::Origin>;
+    /// Number of blocks of sales a median is taken over.
+    type SaleWindow: Get<Self::BlockNumber>;
+    /// Number of blocks of a reporting round, each oracle reports once per round.
+    type ReportInterval: Get<Self::BlockNumber>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as KittyOracle {
+        // tier => floor price as last agreed by a quorum of oracles
+        pub FloorPrices get(fn floor_price): map hasher(twox_64_concat) RarityTier => Option<FloorPrice<BalanceOf<T>, T::BlockNumber>>;
+        // block of the last report
+        pub LastReport get(fn last_report): T::BlockNumber;
+        // reporting round the reports below belong to, block number / `ReportInterval`
+        pub Round get(fn round): T::BlockNumber;
+        // reports of the current round, one per oracle
+        pub RoundReports get(fn round_reports): vec::Vec<(T::AccountId, Report<T>)>;
+        // accounts allowed to report prices
+        pub Oracles get(fn oracles) config(): vec::Vec<T::AccountId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T> where
+        <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+    {
+        /// An oracle reported floor prices. (oracle, [(tier, price)])
+        PricesReported(AccountId, vec::Vec<(RarityTier, Balance)>),
+        /// A quorum of oracles reported, the floor prices are their medians. ([(tier, price)])
+        FloorPricesUpdated(vec::Vec<(RarityTier, Balance)>),
+        OraclesChanged(vec::Vec<AccountId>),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        NotOracle,
+        /// The oracle already reported in this round.
+        AlreadyReported,
+        /// The tiers of a report aren't in ascending order or repeat.
+        UnorderedTiers,
+        /// A price in the report is of no sales.
+        NoSales,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        fn offchain_worker(now: T::BlockNumber) {
+            Self::record_sales(now);
+            Self::send_report(now);
+        }
+
+        /// Report the median price of the recent sales of each tier, `(tier, price, sales)`.
+        ///
+        /// Each oracle reports once per round of `ReportInterval` blocks. Once a majority of
+        /// the oracles reported in a round, the floor price of a tier is the median of their
+        /// prices, for tiers a majority reported. Other tiers keep their last price.
+        ///
+        /// Reports of oracles are free.
+        #[weight = 0]
+        pub fn report_prices(origin, prices: Report<T>) -> DispatchResultWithPostInfo {
+            let oracle = ensure_signed(origin)?;
+
+            let oracles = Self::oracles();
+            ensure!(oracles.contains(&oracle), Error::<T>::NotOracle);
+            ensure!(prices.windows(2).all(|pair| pair[0].0 < pair[1].0), Error::<T>::UnorderedTiers);
+            ensure!(prices.iter().all(|&(_, _, sales)| sales > 0), Error::<T>::NoSales);
+
+            let now = frame_system::Module::<T>::block_number();
+            let round = Self::round_of(now);
+            let mut reports = if Self::round() == round { Self::round_reports() } else { vec::Vec::new() };
+            ensure!(reports.iter().all(|(reporter, _)| *reporter != oracle), Error::<T>::AlreadyReported);
+            reports.push((oracle.clone(), prices.clone()));
+
+            let quorum = oracles.len() / 2 + 1;
+            if reports.len() >= quorum {
+                let floor_prices = Self::aggregate(&reports, quorum);
+                for &(tier, price, sales) in floor_prices.iter() {
+                    <FloorPrices<T>>::insert(tier, FloorPrice { price, sales, updated_at: now });
+                }
+                Self::deposit_event(RawEvent::FloorPricesUpdated(
+                    floor_prices.into_iter().map(|(tier, price, _)| (tier, price)).collect(),
+                ));
+            }
+
+            <Round<T>>::put(round);
+            <RoundReports<T>>::put(reports);
+            <LastReport<T>>::put(now);
+
+            Self::deposit_event(RawEvent::PricesReported(
+                oracle,
+                prices.into_iter().map(|(tier, price, _)| (tier, price)).collect(),
+            ));
+
+            Ok(Pays::No.into())
+        }
+
+        #[weight = 0]
+        pub fn set_oracles(origin, oracles: vec::Vec<T::AccountId>) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            // the quorum changes, the new oracles report afresh
+            <RoundReports<T>>::kill();
+            <Oracles<T>>::put(&oracles);
+            Self::deposit_event(RawEvent::OraclesChanged(oracles));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Floor prices of all tiers reported so far, most common tier first.
+    pub fn floor_prices() -> vec::Vec<(RarityTier, FloorPrice<BalanceOf<T>, T::BlockNumber>)> {
+        RarityTier::ALL.iter()
+            .filter_map(|&tier| Self::floor_price(tier).map(|price| (tier, price)))
+            .collect()
+    }
+
+    /// Reporting round of block `now`.
+    fn round_of(now: T::BlockNumber) -> T::BlockNumber {
+        now / T::ReportInterval::get().max(One::one())
+    }
+
+    /// Whether `oracle` has a report in the reporting round of block `now`.
+    fn reported(oracle: &T::AccountId, now: T::BlockNumber) -> bool {
+        Self::round() == Self::round_of(now) && Self::round_reports().iter().any(|(reporter, _)| reporter == oracle)
+    }
+
+    /// Medians of the prices and sales of each tier in at least `quorum` of `reports`.
+    fn aggregate(reports: &[(T::AccountId, Report<T>)], quorum: usize) -> Report<T> {
+        RarityTier::ALL.iter()
+            .filter_map(|&tier| {
+                let (mut prices, mut sales): (vec::Vec<BalanceOf<T>>, vec::Vec<u32>) = reports.iter()
+                    .filter_map(|(_, report)| report.iter().find(|(report_tier, _, _)| *report_tier == tier))
+                    .map(|&(_, price, sales)| (price, sales))
+                    .unzip();
+                if prices.len() < quorum {
+                    return None;
+                }
+                Some((tier, median(&mut prices)?, median(&mut sales)?))
+            })
+            .collect()
+    }
+
+    /// Remember the sales of block `now` and forget those that left the sale window.
+    fn record_sales(now: T::BlockNumber) {
+        let window = T::SaleWindow::get();
+        let sales: vec::Vec<Sale<T>> = frame_system::Module::<T>::events().into_iter()
+            .filter_map(|record| T::KittySales::convert(record.event))
+            // kitties burned in the block they were sold in are lost
+            .filter_map(|(kitty_id, price)| T::Kitties::dna_of(kitty_id).map(|dna| (now, RarityTier::of(&dna), price)))
+            .collect();
+
+        let result = StorageValueRef::persistent(SALES_KEY).mutate(|stored: Option<Option<vec::Vec<Sale<T>>>>| {
+            let mut recorded = stored.flatten().unwrap_or_default();
+            // a block seen again after a re-org replaces what was recorded for it and its successors
+            recorded.retain(|(block, _, _)| *block < now && block.saturating_add(window) > now);
+            recorded.extend(sales);
+            Ok::<_, ()>(recorded)
+        });
+        if let Ok(Err(_)) = result {
+            debug::warn!("Kitty sales of block {:?} not recorded, another worker updated them", now);
+        }
+    }
+
+    /// Send the medians of the recorded sales, signed by an oracle key of the node, if any.
+    fn send_report(now: T::BlockNumber) {
+        let public = match Self::local_oracle() {
+            Some(public) => public,
+            None => return,
+        };
+
+        // a report sent now is included in the next block at the earliest
+        if Self::reported(&public.clone().into_account(), now.saturating_add(One::one())) {
+            return;
+        }
+
+        // wait for the last report to be included, or dropped, before sending another
+        let reported = StorageValueRef::persistent(REPORTED_KEY);
+        if let Some(Some(at)) = reported.get::<T::BlockNumber>() {
+            if at.saturating_add(T::ReportInterval::get()) > now {
+                return;
+            }
+        }
+
+        let sales = StorageValueRef::persistent(SALES_KEY).get::<vec::Vec<Sale<T>>>()
+            .flatten()
+            .unwrap_or_default();
+        let prices: vec::Vec<_> = RarityTier::ALL.iter()
+            .filter_map(|&tier| {
+                let mut prices: vec::Vec<BalanceOf<T>> = sales.iter()
+                    .filter(|(_, sale_tier, _)| *sale_tier == tier)
+                    .map(|&(_, _, price)| price)
+                    .collect();
+                median(&mut prices).map(|price| (tier, price, prices.len() as u32))
+            })
+            .collect();
+        if prices.is_empty() {
+            return;
+        }
+
+        let result = Signer::<T, T::AuthorityId>::any_account()
+            .with_filter(vec![public])
+            .send_signed_transaction(|_| Call::report_prices(prices.clone()));
+        match result {
+            Some((_, Ok(()))) => reported.set(&now),
+            _ => debug::warn!("Failed to send the kitty floor prices at block {:?}", now),
+        }
+    }
+
+    /// Key of an oracle in the node's keystore, if any.
+    fn local_oracle() -> Option<T::Public> {
+        let oracles = Self::oracles();
+
+        <<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic as RuntimeAppPublic>::all()
+            .into_iter()
+            .map(|key| {
+                let generic: <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic = key.into();
+                generic.into()
+            })
+            .find(|public: &T::Public| oracles.contains(&public.clone().into_account()))
+    }
+}
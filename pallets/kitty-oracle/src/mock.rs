@@ -0,0 +1,183 @@
+use crate::{Call, Module, Trait};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+use frame_system as system;
+use system::offchain::{AppCrypto, CreateSignedTransaction, SendTransactionTypes, SigningTypes};
+use sp_core::H256;
+use sp_runtime::{
+    testing::{Header, TestSignature, TestXt, UintAuthorityId},
+    traits::{BlakeTwo256, Convert, Extrinsic as ExtrinsicT, IdentityLookup},
+    Perbill, Permill,
+};
+
+use balances;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+mod oracle_event {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_kitties<T>,
+        oracle_event<T>,
+        balances<T>,
+    }
+}
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = ();
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for Test {
+    type Balance = u64;
+    type MaxLocks = ();
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<Test>;
+    type WeightInfo = ();
+}
+
+type Randomness = pallet_randomness_collective_flip::Module<Test>;
+
+parameter_types! {
+    pub const NewKittyReserve: u64 = 5_000;
+    pub const MaxBatchSize: u32 = 3;
+    pub const ReuseKittyIds: bool = true;
+    pub const MarketCommission: Permill = Permill::zero();
+}
+
+impl pallet_kitties::Trait for Test {
+    type Event = TestEvent;
+    type KittyIndex = u32;
+    type Randomness = Randomness;
+    type NewKittyReserve = NewKittyReserve;
+    type Currency = balances::Module<Self>;
+    type MaxBatchSize = MaxBatchSize;
+    type OnKittyTransfer = ();
+    type OnKittyBurn = ();
+    type ReuseKittyIds = ReuseKittyIds;
+    type MintOrigin = system::EnsureRoot<u64>;
+    type ForceOrigin = system::EnsureRoot<u64>;
+    type MarketCommission = MarketCommission;
+    type OnCommission = ();
+}
+
+/// Account of the oracle in tests, signing with `UintAuthorityId(ORACLE)`.
+pub const ORACLE: u64 = 3;
+
+pub type Extrinsic = TestXt<Call<Test>, ()>;
+
+impl SigningTypes for Test {
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+}
+
+impl<LocalCall> SendTransactionTypes<LocalCall> for Test where
+    Call<Test>: From<LocalCall>,
+{
+    type OverarchingCall = Call<Test>;
+    type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> CreateSignedTransaction<LocalCall> for Test where
+    Call<Test>: From<LocalCall>,
+{
+    fn create_transaction<C: AppCrypto<Self::Public, Self::Signature>>(
+        call: Call<Test>,
+        _public: UintAuthorityId,
+        account: u64,
+        _nonce: u64,
+    ) -> Option<(Call<Test>, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+        Some((call, (account, ())))
+    }
+}
+
+/// Signs with the keys set by `UintAuthorityId::set_all_keys`.
+pub struct TestAuthorityId;
+impl AppCrypto<UintAuthorityId, TestSignature> for TestAuthorityId {
+    type RuntimeAppPublic = UintAuthorityId;
+    type GenericPublic = UintAuthorityId;
+    type GenericSignature = TestSignature;
+}
+
+pub struct KittySales;
+impl Convert<TestEvent, Option<(u32, u64)>> for KittySales {
+    fn convert(event: TestEvent) -> Option<(u32, u64)> {
+        match event {
+            TestEvent::pallet_kitties(pallet_kitties::RawEvent::Sold(_, _, kitty_id, price)) => Some((kitty_id, price)),
+            _ => None,
+        }
+    }
+}
+
+parameter_types! {
+    pub const SaleWindow: u64 = 10;
+    pub const ReportInterval: u64 = 5;
+}
+
+impl Trait for Test {
+    type Event = TestEvent;
+    type AuthorityId = TestAuthorityId;
+    type Currency = balances::Module<Self>;
+    type KittyIndex = u32;
+    type Kitties = KittiesModule;
+    type KittySales = KittySales;
+    type ForceOrigin = system::EnsureRoot<u64>;
+    type SaleWindow = SaleWindow;
+    type ReportInterval = ReportInterval;
+}
+
+pub type KittyOracle = Module<Test>;
+pub type KittiesModule = pallet_kitties::Module<Test>;
+pub type System = frame_system::Module<Test>;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100000000), (2, 100000000)],
+	}.assimilate_storage(&mut t).unwrap();
+	crate::GenesisConfig::<Test> {
+		oracles: vec![ORACLE],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
+}
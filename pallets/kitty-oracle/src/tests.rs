@@ -0,0 +1,194 @@
+use crate::{mock::*, median, Call, Error, FloorPrice, RarityTier};
+use codec::Decode;
+use frame_support::{assert_noop, assert_ok, traits::OffchainWorker};
+use pallet_kitties::DNA;
+use sp_core::offchain::{testing::{TestOffchainExt, TestTransactionPoolExt}, OffchainExt, TransactionPoolExt};
+use sp_runtime::{testing::UintAuthorityId, DispatchError};
+
+/// Mint a kitty whose DNA starts with `gene` to account 1 and sell it to account 2 for `price`.
+fn sell(gene: u8, price: u64) {
+    let kitty_id = KittiesModule::kitties_count();
+    assert_ok!(KittiesModule::mint(Origin::root(), 1, DNA::new().set([gene; 16])));
+    assert_ok!(KittiesModule::ask(Origin::signed(1), kitty_id, Some(price)));
+    assert_ok!(KittiesModule::buy(Origin::signed(2), kitty_id, price));
+}
+
+#[test]
+fn median_of_prices() {
+    assert_eq!(median::<u64>(&mut []), None);
+    assert_eq!(median(&mut [300u64, 100, 200]), Some(200));
+    assert_eq!(median(&mut [400u64, 100, 200, 300]), Some(250));
+    assert_eq!(median(&mut [u64::max_value(), u64::max_value() - 2]), Some(u64::max_value() - 1));
+}
+
+#[test]
+fn tiers_from_first_dna_byte() {
+    let tier = |gene: u8| RarityTier::of(&DNA::new().set([gene; 16]));
+
+    assert_eq!(tier(0), RarityTier::Common);
+    assert_eq!(tier(159), RarityTier::Common);
+    assert_eq!(tier(160), RarityTier::Uncommon);
+    assert_eq!(tier(224), RarityTier::Rare);
+    assert_eq!(tier(252), RarityTier::Legendary);
+}
+
+#[test]
+fn reports_need_oracle_and_one_per_round() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(5);
+        let prices = vec![(RarityTier::Common, 200, 3), (RarityTier::Legendary, 1000, 1)];
+
+        assert_noop!(KittyOracle::report_prices(Origin::signed(1), prices.clone()), Error::<Test>::NotOracle);
+        assert_noop!(
+            KittyOracle::report_prices(Origin::signed(ORACLE), prices.iter().cloned().rev().collect()),
+            Error::<Test>::UnorderedTiers
+        );
+        assert_noop!(
+            KittyOracle::report_prices(Origin::signed(ORACLE), vec![(RarityTier::Common, 200, 0)]),
+            Error::<Test>::NoSales
+        );
+
+        // a single oracle is its own quorum
+        assert_ok!(KittyOracle::report_prices(Origin::signed(ORACLE), prices.clone()));
+        assert_eq!(KittyOracle::floor_prices(), vec![
+            (RarityTier::Common, FloorPrice { price: 200, sales: 3, updated_at: 5 }),
+            (RarityTier::Legendary, FloorPrice { price: 1000, sales: 1, updated_at: 5 }),
+        ]);
+
+        // rounds are `ReportInterval` blocks long
+        System::set_block_number(9);
+        assert_noop!(KittyOracle::report_prices(Origin::signed(ORACLE), prices.clone()), Error::<Test>::AlreadyReported);
+
+        System::set_block_number(10);
+        assert_ok!(KittyOracle::report_prices(Origin::signed(ORACLE), vec![(RarityTier::Common, 300, 2)]));
+        assert_eq!(KittyOracle::floor_prices(), vec![
+            (RarityTier::Common, FloorPrice { price: 300, sales: 2, updated_at: 10 }),
+            (RarityTier::Legendary, FloorPrice { price: 1000, sales: 1, updated_at: 5 }),
+        ]);
+    })
+}
+
+#[test]
+fn floor_price_is_median_of_quorum() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(5);
+        assert_ok!(KittyOracle::set_oracles(Origin::root(), vec![ORACLE, 4, 5]));
+
+        // one of three oracles is no quorum
+        assert_ok!(KittyOracle::report_prices(Origin::signed(ORACLE), vec![
+            (RarityTier::Common, 200, 3),
+            (RarityTier::Legendary, 1000, 1),
+        ]));
+        assert!(KittyOracle::floor_prices().is_empty());
+
+        // a disagreeing oracle
+        assert_ok!(KittyOracle::report_prices(Origin::signed(4), vec![(RarityTier::Common, 1_000_000, 50)]));
+        assert_eq!(KittyOracle::floor_prices(), vec![
+            (RarityTier::Common, FloorPrice { price: 500_100, sales: 26, updated_at: 5 }),
+        ]);
+
+        // is outvoted by the third
+        assert_ok!(KittyOracle::report_prices(Origin::signed(5), vec![
+            (RarityTier::Common, 210, 4),
+            (RarityTier::Legendary, 900, 1),
+        ]));
+        assert_eq!(KittyOracle::floor_prices(), vec![
+            (RarityTier::Common, FloorPrice { price: 210, sales: 4, updated_at: 5 }),
+            (RarityTier::Legendary, FloorPrice { price: 950, sales: 1, updated_at: 5 }),
+        ]);
+    })
+}
+
+#[test]
+fn oracles_are_set_by_force_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittyOracle::set_oracles(Origin::signed(ORACLE), vec![1]), DispatchError::BadOrigin);
+
+        assert_ok!(KittyOracle::set_oracles(Origin::root(), vec![1]));
+        assert_eq!(KittyOracle::oracles(), vec![1]);
+    })
+}
+
+#[test]
+fn offchain_worker_reports_median_of_window() {
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![ORACLE]);
+
+    t.execute_with(|| {
+        let block = |number: u64, sales: &[(u8, u64)]| {
+            System::set_block_number(number);
+            System::reset_events();
+            for &(gene, price) in sales {
+                sell(gene, price);
+            }
+            KittyOracle::offchain_worker(number);
+            pool_state.write().transactions.pop().map(|tx| Extrinsic::decode(&mut &*tx).unwrap())
+        };
+
+        let tx = block(1, &[(0, 100), (0, 300)]).expect("a report was sent");
+        assert_eq!(tx.signature, Some((ORACLE, ())));
+        assert_eq!(tx.call, Call::report_prices(vec![(RarityTier::Common, 200, 2)]));
+
+        // the report wasn't included, but is only sent again after an interval
+        assert!(block(4, &[(0, 200), (255, 1000)]).is_none());
+
+        let tx = block(6, &[]).expect("a report was sent");
+        assert_eq!(tx.call, Call::report_prices(vec![(RarityTier::Common, 200, 3), (RarityTier::Legendary, 1000, 1)]));
+        assert!(block(8, &[]).is_none());
+
+        // the sales of block 1 left the window
+        let tx = block(11, &[(160, 50)]).expect("a report was sent");
+        assert_eq!(tx.call, Call::report_prices(vec![
+            (RarityTier::Common, 200, 1),
+            (RarityTier::Uncommon, 50, 1),
+            (RarityTier::Legendary, 1000, 1),
+        ]));
+    })
+}
+
+#[test]
+fn offchain_worker_reports_once_per_round() {
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![ORACLE]);
+
+    t.execute_with(|| {
+        System::set_block_number(5);
+        assert_ok!(KittyOracle::report_prices(Origin::signed(ORACLE), vec![(RarityTier::Common, 100, 1)]));
+        sell(0, 100);
+
+        // the next block is in the round the oracle reported in
+        KittyOracle::offchain_worker(5);
+        assert!(pool_state.read().transactions.is_empty());
+
+        System::set_block_number(9);
+        KittyOracle::offchain_worker(9);
+        assert_eq!(pool_state.read().transactions.len(), 1);
+    })
+}
+
+#[test]
+fn offchain_worker_needs_oracle_key() {
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    // a key, but not of an oracle
+    UintAuthorityId::set_all_keys(vec![ORACLE + 1]);
+
+    t.execute_with(|| {
+        System::set_block_number(5);
+        sell(0, 100);
+        KittyOracle::offchain_worker(5);
+
+        assert!(pool_state.read().transactions.is_empty());
+    })
+}
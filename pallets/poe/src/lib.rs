@@ -4,23 +4,165 @@
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// https://substrate.dev/docs/en/knowledgebase/runtime/frame
 
-use frame_support::{decl_module, decl_storage, decl_event,
-					ensure, decl_error, dispatch, traits::{Get}};
-use frame_system::ensure_signed;
+use codec::{Encode, Decode};
+use frame_support::{decl_module, decl_storage, decl_event, debug,
+					ensure, decl_error, dispatch, traits::{Get, EnsureOrigin, Currency, ReservableCurrency, OnUnbalanced, BalanceStatus, ExistenceRequirement},
+					weights::Weight, StoragePrefixedMap, RuntimeDebug, transactional};
+use frame_system::{
+	ensure_signed, ensure_none,
+	offchain::{AppCrypto, SendTransactionTypes, SendUnsignedTransaction, SignedPayload, Signer, SigningTypes},
+};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+	RuntimeAppPublic, SaturatedConversion,
+	offchain::{http, storage::StorageValueRef, Duration},
+	traits::{Hash, IdentifyAccount, Saturating, StaticLookup, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity, ValidTransaction,
+	},
+};
 use sp_std::prelude::*;
 
+pub mod migrations;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+/// Key type of the keys attesters sign availability attestations with.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"poe!");
+
+/// How long the offchain worker waits for the content of a claim.
+const FETCH_TIMEOUT_MS: u64 = 5_000;
+
+/// Maximum number of claims the offchain worker fetches the content of per block.
+const MAX_CHECKS_PER_BLOCK: usize = 5;
+
+/// Maximum number of content URIs the offchain worker looks at per block, due for a check or not.
+const MAX_VISITS_PER_BLOCK: usize = 50;
+
+/// Length of the hash in front of a `blake2_128_concat` key.
+const BLAKE2_128_LEN: usize = 16;
+
+/// Attester keys, sr25519 keys of type `poe!` in the node's keystore.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_runtime::{app_crypto::{app_crypto, sr25519}, MultiSignature, MultiSigner};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs attestations for runtimes whose accounts are `MultiSigner`s.
+	pub struct AttesterId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AttesterId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
 /// Configure the pallet by specifying the parameters and types on which it depends.
-pub trait Trait: frame_system::Trait {
+pub trait Trait: frame_system::Trait + SigningTypes + SendTransactionTypes<Call<Self>> {
 	/// Because this pallet emits events, it depends on the runtime's definition of an event.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 
 	type MaxClaimLength: Get<u32>;
+
+	/// Origin allowed to revoke any claim, e.g. one infringing someone else's rights.
+	type ForceOrigin: EnsureOrigin<Self::Origin>;
+
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// Deposit reserved for every claim, returned when its owner revokes it.
+	type ClaimDeposit: Get<BalanceOf<Self>>;
+
+	/// Receives the deposits of claims revoked through `ForceOrigin`, e.g. the treasury.
+	type Forfeited: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// Maximum number of claims expiring in the same block.
+	type MaxExpiringPerBlock: Get<u32>;
+
+	/// Key the offchain worker signs availability attestations with.
+	type AttesterId: AppCrypto<Self::Public, Self::Signature>;
+
+	/// Maximum length of the content URI of a claim.
+	type MaxUriLength: Get<u32>;
+
+	/// Deposit reserved per byte of a content URI, held along with the claim's deposit.
+	type UriByteDeposit: Get<BalanceOf<Self>>;
+
+	/// Number of blocks an availability attestation is valid for.
+	type AttestationPeriod: Get<Self::BlockNumber>;
+
+	/// Priority of the unsigned attestation transactions.
+	type UnsignedPriority: Get<TransactionPriority>;
+}
+
+/// A claim on some content.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct Proof<AccountId, BlockNumber> {
+	/// Current owner of the claim.
+	pub owner: AccountId,
+	/// Block the claim was created at or last transferred.
+	pub block_number: BlockNumber,
+	/// Block the claim is revoked at, if any.
+	pub expires_at: Option<BlockNumber>,
+}
+
+/// An attester's statement that the content at `uri` hashes to `claim`, signed by `public`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Attestation<Public, BlockNumber> {
+	/// The claim, the hash of the content.
+	pub claim: Vec<u8>,
+	/// URI the content was fetched from.
+	pub uri: Vec<u8>,
+	/// Last block the attestation is valid at.
+	pub valid_until: BlockNumber,
+	/// Key of the attester.
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for Attestation<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Latest accepted attestation of a claim's content.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct AvailabilityRecord<AccountId, BlockNumber> {
+	/// Account of the attester.
+	pub attester: AccountId,
+	/// Block the attestation was included in.
+	pub attested_at: BlockNumber,
+	/// Last block the attestation is valid at.
+	pub valid_until: BlockNumber,
+}
+
+/// Storage layouts of this pallet, in order.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+	/// `Proofs` maps a claim to `(owner, block_number)`.
+	V1_0_0,
+	/// `Proofs` maps a claim to a `Proof`, which can expire.
+	V2_0_0,
+}
+
+impl Releases {
+	/// Layout this version of the pallet reads and writes.
+	pub const LATEST: Releases = Releases::V2_0_0;
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
 }
 
 // The pallet's runtime storage items.
@@ -30,16 +172,51 @@ decl_storage! {
 	// This name may be updated, but each pallet in the runtime must use a unique name.
 	// ---------------------------------vvvvvvvvvvvvvv
 	trait Store for Module<T: Trait> as TemplateModule {
-		Proofs get(fn proofs): map hasher(blake2_128_concat) Vec<u8> => (T::AccountId, T::BlockNumber);
+		Proofs get(fn proofs): map hasher(blake2_128_concat) Vec<u8> => Proof<T::AccountId, T::BlockNumber>;
+
+		// block number => claims expiring at that block. Claims leave the list when revoked or
+		// moved to a new expiry, entries written before that are skipped when the block comes.
+		pub ExpiringClaims get(fn expiring_claims): map hasher(twox_64_concat) T::BlockNumber => Vec<Vec<u8>>;
+
+		// Deposit held for a claim. Claims made before deposits were introduced hold none.
+		pub ClaimDeposits get(fn claim_deposit): map hasher(blake2_128_concat) Vec<u8> => BalanceOf<T>;
+
+		// URI the content of a claim can be fetched from. The claim is the hash of the content.
+		pub ContentUris get(fn content_uri): map hasher(blake2_128_concat) Vec<u8> => Option<Vec<u8>>;
+
+		// Deposit held for the content URI of a claim. URIs set before these deposits hold none.
+		pub ContentDeposits get(fn content_deposit): map hasher(blake2_128_concat) Vec<u8> => BalanceOf<T>;
+
+		// Latest availability attestation of the content of a claim, removed with the URI.
+		pub Availability get(fn availability):
+			map hasher(blake2_128_concat) Vec<u8> => Option<AvailabilityRecord<T::AccountId, T::BlockNumber>>;
+
+		// Accounts whose offchain workers attest the availability of claimed content.
+		pub Attesters get(fn attesters) config(): Vec<T::AccountId>;
+
+		// Storage layout version. Chains started before versioning read as V1_0_0.
+		pub StorageVersion get(fn storage_version) build(|_: &GenesisConfig<T>| Releases::LATEST): Releases;
 	}
 }
 
 // Pallets use events to inform users when important changes are made.
 // https://substrate.dev/docs/en/knowledgebase/runtime/events
 decl_event!(
-	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		BlockNumber = <T as frame_system::Trait>::BlockNumber,
+	{
 		ClaimCreated(AccountId, Vec<u8>),
 		ClaimRevoked(AccountId, Vec<u8>),
+		ClaimExpiryChanged(AccountId, Vec<u8>, Option<BlockNumber>),
+		ClaimExpired(AccountId, Vec<u8>),
+		/// A claim changed hands. (from, to, claim)
+		ClaimTransferred(AccountId, AccountId, Vec<u8>),
+		/// The owner set or cleared the content URI of a claim. (owner, claim, uri)
+		ContentUriChanged(AccountId, Vec<u8>, Option<Vec<u8>>),
+		/// An attester found the content of a claim available. (attester, claim, valid until)
+		ContentAvailable(AccountId, Vec<u8>, BlockNumber),
+		AttestersChanged(Vec<AccountId>),
 	}
 );
 
@@ -50,6 +227,20 @@ decl_error! {
 		ClaimNotExist,
 		NotClaimOwner,
 		ProofTooLong,
+		BalanceNotEnough,
+		ExpiryInPast,
+		/// The claim already expires at that block.
+		ExpiryUnchanged,
+		TooManyExpiringClaims,
+		UriTooLong,
+		NotAttester,
+		AttestationExpired,
+		/// The claim already has an attestation valid as long or longer.
+		AttestationOutdated,
+		/// The attestation is of a URI the claim no longer points at.
+		ContentUriMismatch,
+		/// The owner no longer has the claim's deposit reserved.
+		DepositMissing,
 	}
 }
 
@@ -64,6 +255,18 @@ decl_module! {
 		// Events must be initialized if they are used by the pallet.
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate::<T>()
+		}
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			Self::expire_claims(now)
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			Self::attest_availability(now);
+		}
+
 		#[weight = 0]
 		pub fn create_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
@@ -72,7 +275,15 @@ decl_module! {
 
 			ensure!(T::MaxClaimLength::get() >= claim.len() as u32, Error::<T>::ProofTooLong);
 
-			Proofs::<T>::insert(&claim, (sender.clone(), frame_system::Module::<T>::block_number()));
+			let deposit = T::ClaimDeposit::get();
+			T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::BalanceNotEnough)?;
+
+			Proofs::<T>::insert(&claim, Proof {
+				owner: sender.clone(),
+				block_number: frame_system::Module::<T>::block_number(),
+				expires_at: None,
+			});
+			ClaimDeposits::<T>::insert(&claim, deposit);
 
 			Self::deposit_event(RawEvent::ClaimCreated(sender, claim));
 
@@ -85,11 +296,14 @@ decl_module! {
 
 			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
 
-			let (owner, _block_number) = Proofs::<T>::get(&claim);
+			let proof = Proofs::<T>::get(&claim);
 
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
 
 			Proofs::<T>::remove(&claim);
+			Self::unschedule_expiry(&claim, proof.expires_at);
+			let deposit = ClaimDeposits::<T>::take(&claim).saturating_add(Self::remove_content(&claim));
+			T::Currency::unreserve(&sender, deposit);
 
 			Self::deposit_event(RawEvent::ClaimRevoked(sender, claim));
 
@@ -97,17 +311,376 @@ decl_module! {
 		}
 
 		#[weight = 0]
-		pub fn transfer_claim(origin, claim: Vec<u8>, dest: T::AccountId) -> dispatch::DispatchResult {
+		#[transactional]
+		pub fn transfer_claim(
+			origin,
+			claim: Vec<u8>,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> dispatch::DispatchResult {
 			let sender = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
 
 			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
 
-			let (owner, _block_number) = Proofs::<T>::get(&claim);
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			let proof = Proofs::<T>::get(&claim);
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
 
-			Proofs::<T>::insert(&claim, (dest, frame_system::Module::<T>::block_number()));
+			// the deposits follow the claim
+			let deposit = Self::claim_deposit(&claim).saturating_add(Self::content_deposit(&claim));
+			Self::move_deposit(&sender, &dest, deposit)?;
+
+			// so does the expiry
+			Proofs::<T>::insert(&claim, Proof {
+				owner: dest.clone(),
+				block_number: frame_system::Module::<T>::block_number(),
+				expires_at: proof.expires_at,
+			});
+
+			Self::deposit_event(RawEvent::ClaimTransferred(sender, dest, claim));
 
 			Ok(())
 		}
+
+		#[weight = 0]
+		pub fn force_revoke_claim(origin, claim: Vec<u8>) -> dispatch::DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
+
+			let Proof { owner, expires_at, .. } = Proofs::<T>::take(&claim);
+			Self::unschedule_expiry(&claim, expires_at);
+
+			// the owner loses the deposits
+			let deposit = ClaimDeposits::<T>::take(&claim).saturating_add(Self::remove_content(&claim));
+			let (forfeited, _) = T::Currency::slash_reserved(&owner, deposit);
+			T::Forfeited::on_unbalanced(forfeited);
+
+			Self::deposit_event(RawEvent::ClaimRevoked(owner, claim));
+
+			Ok(())
+		}
+
+		/// Revoke the claim automatically at `expires_at`, or never if `None`.
+		#[weight = 0]
+		pub fn set_claim_expiry(
+			origin,
+			claim: Vec<u8>,
+			expires_at: Option<T::BlockNumber>,
+		) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
+			let mut proof = Proofs::<T>::get(&claim);
+			ensure!(proof.owner == sender, Error::<T>::NotClaimOwner);
+			ensure!(proof.expires_at != expires_at, Error::<T>::ExpiryUnchanged);
+
+			if let Some(block_number) = expires_at {
+				ensure!(block_number > frame_system::Module::<T>::block_number(), Error::<T>::ExpiryInPast);
+				ExpiringClaims::<T>::try_mutate(block_number, |claims| -> dispatch::DispatchResult {
+					// left over from before claims were unscheduled
+					if claims.contains(&claim) {
+						return Ok(());
+					}
+					ensure!((claims.len() as u32) < T::MaxExpiringPerBlock::get(), Error::<T>::TooManyExpiringClaims);
+					claims.push(claim.clone());
+					Ok(())
+				})?;
+			}
+			Self::unschedule_expiry(&claim, proof.expires_at);
+
+			proof.expires_at = expires_at;
+			Proofs::<T>::insert(&claim, proof);
+
+			Self::deposit_event(RawEvent::ClaimExpiryChanged(sender, claim, expires_at));
+
+			Ok(())
+		}
+
+		/// Point the claim at a URI its content can be fetched from, or clear it with `None`.
+		/// Attesters then check that the content hashes to the claim. Reserves `UriByteDeposit`
+		/// per byte of the URI in place of the deposit of the previous one.
+		#[weight = 0]
+		#[transactional]
+		pub fn set_content_uri(origin, claim: Vec<u8>, uri: Option<Vec<u8>>) -> dispatch::DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
+			ensure!(Proofs::<T>::get(&claim).owner == sender, Error::<T>::NotClaimOwner);
+
+			if let Some(uri) = &uri {
+				ensure!(T::MaxUriLength::get() >= uri.len() as u32, Error::<T>::UriTooLong);
+			}
+
+			// attestations were of the old content
+			T::Currency::unreserve(&sender, Self::remove_content(&claim));
+			if let Some(uri) = &uri {
+				let deposit = T::UriByteDeposit::get().saturating_mul((uri.len() as u32).into());
+				T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::BalanceNotEnough)?;
+				ContentUris::insert(&claim, uri);
+				ContentDeposits::<T>::insert(&claim, deposit);
+			}
+
+			Self::deposit_event(RawEvent::ContentUriChanged(sender, claim, uri));
+
+			Ok(())
+		}
+
+		#[weight = 0]
+		pub fn set_attesters(origin, attesters: Vec<T::AccountId>) -> dispatch::DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Attesters::<T>::put(&attesters);
+
+			Self::deposit_event(RawEvent::AttestersChanged(attesters));
+
+			Ok(())
+		}
+
+		/// Record an attestation of an offchain worker, checked by `validate_unsigned`.
+		#[weight = 0]
+		pub fn submit_attestation(
+			origin,
+			attestation: Attestation<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> dispatch::DispatchResult {
+			ensure_none(origin)?;
+
+			let attester = attestation.public.into_account();
+			ensure!(Self::attesters().contains(&attester), Error::<T>::NotAttester);
+
+			let now = frame_system::Module::<T>::block_number();
+			ensure!(attestation.valid_until >= now, Error::<T>::AttestationExpired);
+			ensure!(
+				Self::content_uri(&attestation.claim).as_ref() == Some(&attestation.uri),
+				Error::<T>::ContentUriMismatch
+			);
+			// an attestation that was already recorded, or one older than it, can't be replayed
+			ensure!(
+				Self::availability(&attestation.claim).map_or(true, |record| attestation.valid_until > record.valid_until),
+				Error::<T>::AttestationOutdated
+			);
+
+			Availability::<T>::insert(&attestation.claim, AvailabilityRecord {
+				attester: attester.clone(),
+				attested_at: now,
+				valid_until: attestation.valid_until,
+			});
+
+			Self::deposit_event(RawEvent::ContentAvailable(attester, attestation.claim, attestation.valid_until));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Move exactly `amount` of reserved deposit from `from` to `to`, who may not exist yet.
+	///
+	/// Callers must be transactional, a fresh `to` is funded in several steps.
+	fn move_deposit(from: &T::AccountId, to: &T::AccountId, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+
+		if T::Currency::total_balance(to).is_zero() {
+			// `repatriate_reserved` refuses accounts that don't exist yet
+			ensure!(T::Currency::unreserve(from, amount).is_zero(), Error::<T>::DepositMissing);
+			T::Currency::transfer(from, to, amount, ExistenceRequirement::AllowDeath)?;
+			T::Currency::reserve(to, amount)?;
+		} else {
+			let leftover = T::Currency::repatriate_reserved(from, to, amount, BalanceStatus::Reserved)?;
+			ensure!(leftover.is_zero(), Error::<T>::DepositMissing);
+		}
+
+		Ok(())
+	}
+
+	/// Take `claim` off the list of claims expiring at `expires_at`.
+	fn unschedule_expiry(claim: &[u8], expires_at: Option<T::BlockNumber>) {
+		if let Some(block_number) = expires_at {
+			ExpiringClaims::<T>::mutate_exists(block_number, |claims| {
+				if let Some(list) = claims {
+					list.retain(|expiring| expiring.as_slice() != claim);
+					if list.is_empty() {
+						*claims = None;
+					}
+				}
+			});
+		}
+	}
+
+	/// Revoke the claims expiring at `now` and return their deposits.
+	fn expire_claims(now: T::BlockNumber) -> Weight {
+		let claims = ExpiringClaims::<T>::take(now);
+		let mut expired: Weight = 0;
+
+		for claim in claims.iter() {
+			if !Proofs::<T>::contains_key(claim) {
+				continue;
+			}
+			let proof = Proofs::<T>::get(claim);
+			if proof.expires_at != Some(now) {
+				continue;
+			}
+
+			Proofs::<T>::remove(claim);
+			let deposit = ClaimDeposits::<T>::take(claim).saturating_add(Self::remove_content(claim));
+			T::Currency::unreserve(&proof.owner, deposit);
+			Self::deposit_event(RawEvent::ClaimExpired(proof.owner, claim.clone()));
+			expired += 1;
+		}
+
+		T::DbWeight::get().reads_writes(1 + claims.len() as Weight + expired * 3, 1 + expired * 6)
+	}
+
+	/// Forget the content URI of a claim and its attestation. Returns the deposit held for the
+	/// URI, still reserved from the owner.
+	fn remove_content(claim: &[u8]) -> BalanceOf<T> {
+		ContentUris::remove(claim);
+		Availability::<T>::remove(claim);
+		ContentDeposits::<T>::take(claim)
+	}
+
+	/// Fetch the content of claims due for a check and attest the ones it hashes to. Does
+	/// nothing unless the node holds the key of an attester.
+	///
+	/// Every block looks at the next `MAX_VISITS_PER_BLOCK` URIs after the last one looked at,
+	/// starting over from the first once all were.
+	fn attest_availability(now: T::BlockNumber) {
+		let public = match Self::local_attester() {
+			Some(public) => public,
+			None => return,
+		};
+		let period = T::AttestationPeriod::get();
+		let mut checked = 0;
+
+		let prefix = ContentUris::final_prefix();
+		let cursor = StorageValueRef::persistent(b"poe::check_cursor");
+		let mut key = cursor.get::<Vec<u8>>()
+			.flatten()
+			.filter(|key| key.starts_with(&prefix))
+			.unwrap_or_else(|| prefix.to_vec());
+
+		for _ in 0..MAX_VISITS_PER_BLOCK {
+			if checked == MAX_CHECKS_PER_BLOCK {
+				break;
+			}
+			key = match sp_io::storage::next_key(&key).filter(|next| next.starts_with(&prefix)) {
+				Some(next) => next,
+				None => {
+					key = prefix.to_vec();
+					break;
+				},
+			};
+
+			let claim = match key.get(prefix.len() + BLAKE2_128_LEN..).and_then(|mut raw| Vec::<u8>::decode(&mut raw).ok()) {
+				Some(claim) => claim,
+				None => continue,
+			};
+			let uri = match Self::content_uri(&claim) {
+				Some(uri) => uri,
+				None => continue,
+			};
+
+			// Each claim is checked every half period, whatever the outcome, which renews its
+			// attestation before it lapses without fetching the content every block.
+			let next_check = StorageValueRef::persistent(&(b"poe::next_check", &claim, &uri).encode());
+			if let Some(Some(at)) = next_check.get::<T::BlockNumber>() {
+				if at > now {
+					continue;
+				}
+			}
+			next_check.set(&now.saturating_add(period / T::BlockNumber::from(2u32)));
+			checked += 1;
+
+			match Self::fetch_content(&uri) {
+				Ok(content) if T::Hashing::hash(&content).as_ref() == &claim[..] => {
+					let result = Signer::<T, T::AttesterId>::any_account()
+						.with_filter(vec![public.clone()])
+						.send_unsigned_transaction(
+							|account| Attestation {
+								claim: claim.clone(),
+								uri: uri.clone(),
+								valid_until: now.saturating_add(period),
+								public: account.public.clone(),
+							},
+							|attestation, signature| Call::submit_attestation(attestation, signature),
+						);
+					if let Some((_, Err(()))) = result {
+						debug::warn!("Failed to submit the attestation of claim {:?}", claim);
+					}
+				},
+				Ok(_) => debug::info!("Content of claim {:?} doesn't hash to the claim", claim),
+				Err(e) => debug::info!("Failed to fetch the content of claim {:?}: {:?}", claim, e),
+			}
+		}
+
+		cursor.set(&key);
+	}
+
+	/// Key of an attester in the node's keystore, if any.
+	fn local_attester() -> Option<T::Public> {
+		let attesters = Self::attesters();
+
+		<<T::AttesterId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic as RuntimeAppPublic>::all()
+			.into_iter()
+			.map(|key| {
+				let generic: <T::AttesterId as AppCrypto<T::Public, T::Signature>>::GenericPublic = key.into();
+				generic.into()
+			})
+			.find(|public: &T::Public| attesters.contains(&public.clone().into_account()))
+	}
+
+	/// Content served at `uri`.
+	fn fetch_content(uri: &[u8]) -> Result<Vec<u8>, http::Error> {
+		let uri = sp_std::str::from_utf8(uri).map_err(|_| http::Error::Unknown)?;
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_MS));
+
+		let pending = http::Request::get(uri).deadline(deadline).send().map_err(|_| http::Error::IoError)?;
+		let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+		if response.code != 200 {
+			return Err(http::Error::Unknown);
+		}
+
+		Ok(response.body().collect())
+	}
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		let (attestation, signature) = match call {
+			Call::submit_attestation(attestation, signature) => (attestation, signature),
+			_ => return InvalidTransaction::Call.into(),
+		};
+
+		let now = frame_system::Module::<T>::block_number();
+		if attestation.valid_until < now {
+			return InvalidTransaction::Stale.into();
+		}
+		if attestation.valid_until > now.saturating_add(T::AttestationPeriod::get()) {
+			return InvalidTransaction::Future.into();
+		}
+		if !Self::attesters().contains(&attestation.public.clone().into_account())
+			|| !SignedPayload::<T>::verify::<T::AttesterId>(attestation, signature.clone())
+		{
+			return InvalidTransaction::BadProof.into();
+		}
+		if Self::content_uri(&attestation.claim).as_ref() != Some(&attestation.uri) {
+			return InvalidTransaction::Call.into();
+		}
+		if let Some(record) = Self::availability(&attestation.claim) {
+			if attestation.valid_until <= record.valid_until {
+				return InvalidTransaction::Stale.into();
+			}
+		}
+
+		ValidTransaction::with_tag_prefix("PoeAttestation")
+			.priority(T::UnsignedPriority::get())
+			.and_provides((&attestation.claim, &attestation.public))
+			.longevity((attestation.valid_until - now).saturated_into::<u64>().max(1))
+			.propagate(true)
+			.build()
 	}
 }
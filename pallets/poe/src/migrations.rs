@@ -0,0 +1,58 @@
+//! Storage migrations for the proof of existence pallet.
+//!
+//! `migrate` runs from `on_runtime_upgrade` and walks `StorageVersion` up to `Releases::LATEST`.
+//! `pre_migrate` and `post_migrate` are meant for dry runs against exported state, they read
+//! the whole pallet storage and must not be called on chain.
+
+use frame_support::{
+	ensure, IterableStorageMap, StorageValue,
+	storage::migration::StorageIterator,
+	traits::Get,
+	weights::Weight,
+};
+use sp_std::cell::Cell;
+use crate::*;
+
+const MODULE: &[u8] = b"TemplateModule";
+
+/// Bring storage up to `Releases::LATEST`.
+pub fn migrate<T: Trait>() -> Weight {
+	let mut weight = T::DbWeight::get().reads(1);
+
+	if Module::<T>::storage_version() < Releases::V2_0_0 {
+		weight = weight.saturating_add(migrate_to_v2::<T>());
+		StorageVersion::put(Releases::V2_0_0);
+		weight = weight.saturating_add(T::DbWeight::get().writes(1));
+	}
+
+	weight
+}
+
+/// Turn `(owner, block_number)` proofs into `Proof`s that never expire.
+fn migrate_to_v2<T: Trait>() -> Weight {
+	let proofs = Cell::new(0 as Weight);
+
+	Proofs::<T>::translate::<(T::AccountId, T::BlockNumber), _>(|_, (owner, block_number)| {
+		proofs.set(proofs.get() + 1);
+		Some(Proof { owner, block_number, expires_at: None })
+	});
+
+	T::DbWeight::get().reads_writes(proofs.get(), proofs.get())
+}
+
+/// Checks before `migrate`: storage must not be newer than this runtime.
+pub fn pre_migrate<T: Trait>() -> Result<(), &'static str> {
+	ensure!(Module::<T>::storage_version() <= Releases::LATEST, "poe storage is newer than this runtime");
+	Ok(())
+}
+
+/// Checks after `migrate`: storage is at the latest version and every proof decodes.
+pub fn post_migrate<T: Trait>() -> Result<(), &'static str> {
+	ensure!(Module::<T>::storage_version() == Releases::LATEST, "poe storage version was not bumped");
+	ensure!(
+		StorageIterator::<()>::new(MODULE, b"Proofs").count() == Proofs::<T>::iter().count(),
+		"some proofs don't decode"
+	);
+
+	Ok(())
+}
@@ -1,10 +1,12 @@
-use crate::{Module, Trait};
+use crate::{Call, Module, Trait};
 use sp_core::H256;
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{impl_outer_origin, parameter_types, weights::Weight, traits::{OnUnbalanced, Currency}};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, TestXt, UintAuthorityId}, Perbill,
+	transaction_validity::TransactionPriority,
 };
 use frame_system as system;
+use system::offchain::{AppCrypto, SendTransactionTypes, SigningTypes};
 
 impl_outer_origin! {
 	pub enum Origin for Test {}
@@ -43,24 +45,97 @@ impl system::Trait for Test {
 	type AvailableBlockRatio = AvailableBlockRatio;
 	type Version = ();
 	type PalletInfo = ();
-	type AccountData = ();
+	type AccountData = balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl balances::Trait for Test {
+	type Balance = u64;
+	type MaxLocks = ();
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = system::Module<Test>;
+	type WeightInfo = ();
+}
+
 parameter_types! {
     pub const MaxClaimLength: u32 = 6;
+    pub const ClaimDeposit: u64 = 100;
+    pub const MaxExpiringPerBlock: u32 = 2;
+    pub const MaxUriLength: u32 = 32;
+    pub const UriByteDeposit: u64 = 1;
+    pub const AttestationPeriod: u64 = 10;
+    pub const UnsignedPriority: TransactionPriority = 1 << 20;
+}
+
+/// Account of the attester in tests, signing with `UintAuthorityId(ATTESTER)`.
+pub const ATTESTER: u64 = 3;
+
+pub type Extrinsic = TestXt<Call<Test>, ()>;
+
+impl SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> SendTransactionTypes<LocalCall> for Test where
+	Call<Test>: From<LocalCall>,
+{
+	type OverarchingCall = Call<Test>;
+	type Extrinsic = Extrinsic;
+}
+
+/// Signs with the keys set by `UintAuthorityId::set_all_keys`.
+pub struct TestAttesterId;
+impl AppCrypto<UintAuthorityId, TestSignature> for TestAttesterId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+/// Account collecting forfeited deposits in tests.
+pub const FORFEIT_POT: u64 = 99;
+
+pub struct ForfeitPot;
+impl OnUnbalanced<balances::NegativeImbalance<Test>> for ForfeitPot {
+	fn on_nonzero_unbalanced(amount: balances::NegativeImbalance<Test>) {
+		Balances::resolve_creating(&FORFEIT_POT, amount);
+	}
 }
 
 impl Trait for Test {
 	type Event = ();
 	type MaxClaimLength = MaxClaimLength;
+	type ForceOrigin = system::EnsureRoot<u64>;
+	type Currency = Balances;
+	type ClaimDeposit = ClaimDeposit;
+	type Forfeited = ForfeitPot;
+	type MaxExpiringPerBlock = MaxExpiringPerBlock;
+	type AttesterId = TestAttesterId;
+	type MaxUriLength = MaxUriLength;
+	type UriByteDeposit = UriByteDeposit;
+	type AttestationPeriod = AttestationPeriod;
+	type UnsignedPriority = UnsignedPriority;
 }
 
 pub type PoeModule = Module<Test>;
+pub type Balances = balances::Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	balances::GenesisConfig::<Test> {
+		balances: vec![(1, 10_000), (2, 10_000)],
+	}.assimilate_storage(&mut t).unwrap();
+	crate::GenesisConfig::<Test> {
+		attesters: vec![ATTESTER],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
 }
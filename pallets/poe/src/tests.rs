@@ -1,5 +1,10 @@
 use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{assert_ok, assert_noop, traits::{OffchainWorker, OnInitialize}, unsigned::ValidateUnsigned};
+use sp_core::offchain::{
+    testing::{PendingRequest, TestOffchainExt, TestTransactionPoolExt},
+    OffchainExt, TransactionPoolExt,
+};
+use sp_runtime::{testing::{TestSignature, UintAuthorityId}, traits::BlakeTwo256};
 use super::*;
 
 #[test]
@@ -7,7 +12,11 @@ fn create_claim_works() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
-        assert_eq!(Proofs::<Test>::get(&claim), (1, frame_system::Module::<Test>::block_number()));
+        assert_eq!(Proofs::<Test>::get(&claim), Proof {
+            owner: 1,
+            block_number: frame_system::Module::<Test>::block_number(),
+            expires_at: None,
+        });
     })
 }
 
@@ -78,7 +87,56 @@ fn transfer_claim_works() {
         let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
 
         assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
-        assert_eq!(Proofs::<Test>::get(&claim), (2, frame_system::Module::<Test>::block_number()));
+        assert_eq!(Proofs::<Test>::get(&claim), Proof {
+            owner: 2,
+            block_number: frame_system::Module::<Test>::block_number(),
+            expires_at: None,
+        });
+    })
+}
+
+#[test]
+fn transfer_claim_to_fresh_account_moves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 10));
+        assert_eq!(Proofs::<Test>::get(&claim).owner, 10);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 10_000 - 100);
+        assert_eq!(Balances::reserved_balance(10), 100);
+    })
+}
+
+#[test]
+fn transfer_claim_moves_only_its_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+        let _ = PoeModule::create_claim(Origin::signed(1), vec![2, 3]);
+
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_eq!(Balances::reserved_balance(1), 100);
+        assert_eq!(Balances::reserved_balance(2), 100);
+    })
+}
+
+#[test]
+fn transfer_claim_failed_when_deposit_missing() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+        Balances::unreserve(&1, 40);
+
+        assert_noop!(
+            PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2),
+            Error::<Test>::DepositMissing
+        );
+        assert_noop!(
+            PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 10),
+            Error::<Test>::DepositMissing
+        );
     })
 }
 
@@ -105,4 +163,399 @@ fn transfer_claim_failed_with_wrong_owner() {
             Error::<Test>::NotClaimOwner
         );
     })
-}
\ No newline at end of file
+}
+
+#[test]
+fn migration_checks_pass() {
+    new_test_ext().execute_with(|| {
+        let _ = PoeModule::create_claim(Origin::signed(1), vec![0, 1]);
+
+        assert_ok!(migrations::pre_migrate::<Test>());
+        migrations::migrate::<Test>();
+        assert_ok!(migrations::post_migrate::<Test>());
+        assert_eq!(PoeModule::storage_version(), Releases::LATEST);
+    })
+}
+
+#[test]
+fn force_revoke_claim_works() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_claim(Origin::signed(1), claim.clone());
+
+        assert_noop!(
+            PoeModule::force_revoke_claim(Origin::signed(2), claim.clone()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        assert_ok!(PoeModule::force_revoke_claim(Origin::root(), claim.clone()));
+        assert!(!Proofs::<Test>::contains_key(&claim));
+        // the deposit is forfeited
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 10_000 - 100);
+        assert_eq!(Balances::free_balance(FORFEIT_POT), 100);
+
+        assert_noop!(
+            PoeModule::force_revoke_claim(Origin::root(), claim.clone()),
+            Error::<Test>::ClaimNotExist
+        );
+    })
+}
+
+#[test]
+fn claim_deposit_is_reserved_and_returned() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(3), claim.clone()),
+            Error::<Test>::BalanceNotEnough
+        );
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 10_000);
+    })
+}
+
+#[test]
+fn claim_deposit_follows_transfer() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 100);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(Balances::free_balance(2), 10_100);
+    })
+}
+
+#[test]
+fn migration_turns_tuples_into_proofs() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        StorageVersion::put(Releases::V1_0_0);
+        frame_support::storage::unhashed::put(&Proofs::<Test>::hashed_key_for(&claim), &(1u64, 5u64));
+
+        assert_ok!(migrations::pre_migrate::<Test>());
+        migrations::migrate::<Test>();
+        assert_ok!(migrations::post_migrate::<Test>());
+        assert_eq!(Proofs::<Test>::get(&claim), Proof { owner: 1, block_number: 5, expires_at: None });
+    })
+}
+
+#[test]
+fn claim_expires_and_returns_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+        assert_noop!(
+            PoeModule::set_claim_expiry(Origin::signed(2), claim.clone(), Some(5)),
+            Error::<Test>::NotClaimOwner
+        );
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), claim.clone(), Some(5)));
+        assert_eq!(Proofs::<Test>::get(&claim).expires_at, Some(5));
+
+        PoeModule::on_initialize(4);
+        assert!(Proofs::<Test>::contains_key(&claim));
+
+        PoeModule::on_initialize(5);
+        assert!(!Proofs::<Test>::contains_key(&claim));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 10_000);
+    })
+}
+
+#[test]
+fn cleared_or_moved_expiry_is_skipped() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), claim.clone(), Some(5)));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), claim.clone(), Some(8)));
+
+        PoeModule::on_initialize(5);
+        assert!(Proofs::<Test>::contains_key(&claim));
+
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), claim.clone(), None));
+        PoeModule::on_initialize(8);
+        assert!(Proofs::<Test>::contains_key(&claim));
+        assert_eq!(Balances::reserved_balance(1), 100);
+    })
+}
+
+#[test]
+fn moved_or_revoked_claim_leaves_expiring_list() {
+    new_test_ext().execute_with(|| {
+        for claim in vec![vec![0], vec![1], vec![2]] {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), claim));
+        }
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(5)));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![1], Some(5)));
+
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(8)));
+        assert_eq!(PoeModule::expiring_claims(5), vec![vec![1]]);
+        assert_eq!(PoeModule::expiring_claims(8), vec![vec![0]]);
+
+        // the freed slot can be taken
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![2], Some(5)));
+        assert_eq!(PoeModule::expiring_claims(5), vec![vec![1], vec![2]]);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), vec![1]));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![2], None));
+        assert!(!ExpiringClaims::<Test>::contains_key(5));
+    })
+}
+
+#[test]
+fn claim_is_listed_once_per_expiry() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), vec![0]));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(5)));
+
+        assert_noop!(
+            PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(5)),
+            Error::<Test>::ExpiryUnchanged
+        );
+
+        // an entry left over from before claims were unscheduled isn't pushed again
+        ExpiringClaims::<Test>::insert(8, vec![vec![0]]);
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(8)));
+        assert_eq!(PoeModule::expiring_claims(8), vec![vec![0]]);
+        assert!(!ExpiringClaims::<Test>::contains_key(5));
+    })
+}
+
+#[test]
+fn claim_expiry_must_be_in_future_and_within_limit() {
+    new_test_ext().execute_with(|| {
+        frame_system::Module::<Test>::set_block_number(3);
+        for claim in vec![vec![0], vec![1], vec![2]] {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), claim));
+        }
+
+        assert_noop!(
+            PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(3)),
+            Error::<Test>::ExpiryInPast
+        );
+
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![0], Some(5)));
+        assert_ok!(PoeModule::set_claim_expiry(Origin::signed(1), vec![1], Some(5)));
+        assert_noop!(
+            PoeModule::set_claim_expiry(Origin::signed(1), vec![2], Some(5)),
+            Error::<Test>::TooManyExpiringClaims
+        );
+    })
+}
+
+/// Claim of account 1 on `content`, its hash, served at `uri`. Inserted directly, the hash is
+/// longer than `MaxClaimLength`.
+fn claim_content(content: &[u8], uri: &[u8]) -> Vec<u8> {
+    let claim = BlakeTwo256::hash(content).as_ref().to_vec();
+    Proofs::<Test>::insert(&claim, Proof { owner: 1, block_number: 0, expires_at: None });
+    assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(uri.to_vec())));
+    claim
+}
+
+/// `attestation` and the signature of the attester.
+fn signed(attestation: Attestation<UintAuthorityId, u64>) -> Call<Test> {
+    let signature = SignedPayload::<Test>::sign::<TestAttesterId>(&attestation).expect("test keys sign");
+    Call::submit_attestation(attestation, signature)
+}
+
+#[test]
+fn set_content_uri_works() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+
+        assert_noop!(
+            PoeModule::set_content_uri(Origin::signed(2), claim.clone(), Some(b"http://a".to_vec())),
+            Error::<Test>::NotClaimOwner
+        );
+        assert_noop!(
+            PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(vec![b'a'; 33])),
+            Error::<Test>::UriTooLong
+        );
+
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(b"http://a".to_vec())));
+        assert_eq!(PoeModule::content_uri(&claim), Some(b"http://a".to_vec()));
+        assert_eq!(Balances::reserved_balance(1), 100 + 8);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(PoeModule::content_uri(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    })
+}
+
+// the URI deposit is replaced with the URI and goes wherever the claim's deposit goes
+#[test]
+fn content_deposit_follows_claim() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(b"http://a".to_vec())));
+
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(b"http://ab".to_vec())));
+        assert_eq!(Balances::reserved_balance(1), 100 + 9);
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), None));
+        assert_eq!(Balances::reserved_balance(1), 100);
+
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(b"http://a".to_vec())));
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 100 + 8);
+
+        assert_ok!(PoeModule::force_revoke_claim(Origin::root(), claim.clone()));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(FORFEIT_POT), 100 + 8);
+    })
+}
+
+#[test]
+fn offchain_worker_attests_matching_content() {
+    let (offchain, offchain_state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![ATTESTER]);
+
+    offchain_state.write().expect_request(PendingRequest {
+        method: "GET".into(),
+        uri: "http://localhost/hello".into(),
+        response: Some(b"hello".to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+
+    t.execute_with(|| {
+        let claim = claim_content(b"hello", b"http://localhost/hello");
+        PoeModule::offchain_worker(1);
+
+        let tx = pool_state.write().transactions.pop().expect("an attestation was submitted");
+        let tx = Extrinsic::decode(&mut &*tx).unwrap();
+        assert_eq!(tx.signature, None);
+        match tx.call {
+            Call::submit_attestation(attestation, _) => assert_eq!(attestation, Attestation {
+                claim: claim.clone(),
+                uri: b"http://localhost/hello".to_vec(),
+                valid_until: 11,
+                public: UintAuthorityId(ATTESTER),
+            }),
+            call => panic!("unexpected call {:?}", call),
+        }
+
+        // the content is fetched again only after half the attestation period
+        PoeModule::offchain_worker(2);
+        assert!(pool_state.read().transactions.is_empty());
+    })
+}
+
+#[test]
+fn offchain_worker_skips_other_content() {
+    let (offchain, offchain_state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    UintAuthorityId::set_all_keys(vec![ATTESTER]);
+
+    offchain_state.write().expect_request(PendingRequest {
+        method: "GET".into(),
+        uri: "http://localhost/hello".into(),
+        response: Some(b"goodbye".to_vec()),
+        sent: true,
+        ..Default::default()
+    });
+
+    t.execute_with(|| {
+        claim_content(b"hello", b"http://localhost/hello");
+        PoeModule::offchain_worker(1);
+
+        assert!(pool_state.read().transactions.is_empty());
+    })
+}
+
+#[test]
+fn offchain_worker_needs_attester_key() {
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut t = new_test_ext();
+    t.register_extension(OffchainExt::new(offchain));
+    t.register_extension(TransactionPoolExt::new(pool));
+    // a key, but not of an attester
+    UintAuthorityId::set_all_keys(vec![ATTESTER + 1]);
+
+    t.execute_with(|| {
+        claim_content(b"hello", b"http://localhost/hello");
+        PoeModule::offchain_worker(1);
+
+        assert!(pool_state.read().transactions.is_empty());
+    })
+}
+
+#[test]
+fn attestations_are_validated_and_recorded() {
+    new_test_ext().execute_with(|| {
+        let claim = claim_content(b"hello", b"http://localhost/hello");
+        let attestation = |public, valid_until| Attestation {
+            claim: claim.clone(),
+            uri: b"http://localhost/hello".to_vec(),
+            valid_until,
+            public: UintAuthorityId(public),
+        };
+        let validate = |call| PoeModule::validate_unsigned(TransactionSource::External, &call);
+        frame_system::Module::<Test>::set_block_number(5);
+
+        assert_eq!(validate(signed(attestation(ATTESTER + 1, 15))), InvalidTransaction::BadProof.into());
+        assert_eq!(validate(signed(attestation(ATTESTER, 4))), InvalidTransaction::Stale.into());
+        assert_eq!(validate(signed(attestation(ATTESTER, 16))), InvalidTransaction::Future.into());
+        let forged = Call::submit_attestation(attestation(ATTESTER, 15), TestSignature(ATTESTER, vec![]));
+        assert_eq!(validate(forged), InvalidTransaction::BadProof.into());
+
+        let call = signed(attestation(ATTESTER, 15));
+        assert_ok!(validate(call.clone()));
+        if let Call::submit_attestation(attestation, signature) = call {
+            assert_ok!(PoeModule::submit_attestation(Origin::none(), attestation, signature));
+        }
+        assert_eq!(PoeModule::availability(&claim), Some(AvailabilityRecord {
+            attester: ATTESTER,
+            attested_at: 5,
+            valid_until: 15,
+        }));
+
+        // the recorded attestation can't be replayed, only a later one renews it
+        let replayed = signed(attestation(ATTESTER, 15));
+        assert_eq!(validate(replayed.clone()), InvalidTransaction::Stale.into());
+        if let Call::submit_attestation(attestation, signature) = replayed {
+            assert_noop!(
+                PoeModule::submit_attestation(Origin::none(), attestation, signature),
+                Error::<Test>::AttestationOutdated
+            );
+        }
+        frame_system::Module::<Test>::set_block_number(6);
+        assert_eq!(validate(signed(attestation(ATTESTER, 14))), InvalidTransaction::Stale.into());
+        assert_ok!(validate(signed(attestation(ATTESTER, 16))));
+
+        // a new URI drops the attestation and invalidates pending ones
+        assert_ok!(PoeModule::set_content_uri(Origin::signed(1), claim.clone(), Some(b"http://b".to_vec())));
+        assert_eq!(PoeModule::availability(&claim), None);
+        assert_eq!(validate(signed(attestation(ATTESTER, 15))), InvalidTransaction::Call.into());
+    })
+}
+
+// the verifier computes the keys the pallet stores claims under
+#[test]
+fn verifier_claim_key_matches_storage() {
+    for claim in vec![vec![], vec![0, 1], vec![7; 100]] {
+        assert_eq!(node_template_proof_verifier::claim_key(&claim), Proofs::<Test>::hashed_key_for(&claim));
+    }
+}
@@ -0,0 +1,195 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Lets signed accounts schedule their own calls for a later block.
+//!
+//! Calls go through `T::Scheduler`, usually `pallet_scheduler`, and are dispatched with the
+//! signed origin of the account that scheduled them. `CallFilter` decides which calls may be
+//! scheduled at all, and every account may have at most `MaxScheduledPerAccount` calls pending,
+//! at most `MaxScheduleDelay` blocks ahead. Each pending call holds `ScheduleDeposit` reserved,
+//! released once it is dispatched or canceled.
+
+use codec::Encode;
+use frame_support::{decl_module, decl_storage, decl_event, dispatch,
+                    ensure, decl_error, Parameter, StorageMap, StorageValue, transactional,
+                    traits::{Get, Filter, Currency, ReservableCurrency, schedule::{ScheduleNamed, DispatchTime, LOWEST_PRIORITY}},
+                    weights::Weight,
+};
+use frame_system::ensure_signed;
+use sp_runtime::traits::Saturating;
+use sp_std::prelude::*;
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Prefix of the names calls are scheduled under, so they can't clash with other pallets'.
+const TASK_PREFIX: &[u8] = b"ScheduledCalls";
+
+pub type ScheduleId = u64;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Calls that can be scheduled, usually the runtime's `Call`.
+    type ScheduledCall: Parameter;
+    /// Calls accounts are allowed to schedule.
+    type CallFilter: Filter<Self::ScheduledCall>;
+    /// Origin calls are dispatched with, usually the runtime's `OriginCaller`.
+    type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+    type Scheduler: ScheduleNamed<Self::BlockNumber, Self::ScheduledCall, Self::PalletsOrigin>;
+    /// Maximum number of calls an account may have scheduled at once.
+    type MaxScheduledPerAccount: Get<u32>;
+    /// Maximum number of blocks ahead a call may be scheduled.
+    type MaxScheduleDelay: Get<Self::BlockNumber>;
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Reserved for every pending call.
+    type ScheduleDeposit: Get<BalanceOf<Self>>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as ScheduledCalls {
+        pub NextScheduleId get(fn next_schedule_id): ScheduleId;
+        // schedule id => (owner, block the call is dispatched at)
+        pub Schedules get(fn schedules): map hasher(twox_64_concat) ScheduleId => Option<(T::AccountId, T::BlockNumber)>;
+        // schedule id => deposit reserved from its owner, none for calls scheduled before deposits
+        pub ScheduleDeposits get(fn schedule_deposit): map hasher(twox_64_concat) ScheduleId => BalanceOf<T>;
+        // account => number of calls it has pending
+        pub ScheduledCount get(fn scheduled_count): map hasher(blake2_128_concat) T::AccountId => u32;
+        // block number => schedules dispatched at that block, released in `on_initialize`
+        Agenda get(fn agenda): map hasher(twox_64_concat) T::BlockNumber => Vec<ScheduleId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T> where
+        AccountId = <T as frame_system::Trait>::AccountId,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
+    {
+        /// A call is scheduled. (owner, schedule_id, when)
+        Scheduled(AccountId, ScheduleId, BlockNumber),
+        /// A scheduled call is canceled. (owner, schedule_id)
+        Canceled(AccountId, ScheduleId),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        CallNotAllowed,
+        ScheduleInPast,
+        /// `when` is more than `MaxScheduleDelay` blocks ahead.
+        ScheduleTooFar,
+        /// The sender can't reserve the deposit.
+        BalanceNotEnough,
+        TooManyScheduled,
+        ScheduleIdOverflow,
+        /// The scheduler refused the call.
+        FailedToSchedule,
+        ScheduleNotExist,
+        NotScheduleOwner,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Errors must be initialized if they are used by the pallet.
+        type Error = Error<T>;
+
+        // Events must be initialized if they are used by the pallet.
+        fn deposit_event() = default;
+
+        const MaxScheduledPerAccount: u32 = T::MaxScheduledPerAccount::get();
+
+        const MaxScheduleDelay: T::BlockNumber = T::MaxScheduleDelay::get();
+
+        const ScheduleDeposit: BalanceOf<T> = T::ScheduleDeposit::get();
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::release(now)
+        }
+
+        /// Dispatch `call` at block `when`, signed by the sender. Reserves `ScheduleDeposit` until then.
+        #[weight = 0]
+        #[transactional]
+        pub fn schedule(origin, when: T::BlockNumber, call: Box<T::ScheduledCall>) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            ensure!(T::CallFilter::filter(&call), Error::<T>::CallNotAllowed);
+            let now = frame_system::Module::<T>::block_number();
+            ensure!(when > now, Error::<T>::ScheduleInPast);
+            ensure!(when <= now.saturating_add(T::MaxScheduleDelay::get()), Error::<T>::ScheduleTooFar);
+
+            let count = Self::scheduled_count(&sender);
+            ensure!(count < T::MaxScheduledPerAccount::get(), Error::<T>::TooManyScheduled);
+
+            let id = Self::next_schedule_id();
+            let next_id = id.checked_add(1).ok_or(Error::<T>::ScheduleIdOverflow)?;
+
+            let deposit = T::ScheduleDeposit::get();
+            T::Currency::reserve(&sender, deposit).map_err(|_| Error::<T>::BalanceNotEnough)?;
+
+            T::Scheduler::schedule_named(
+                Self::task_name(id),
+                DispatchTime::At(when),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Signed(sender.clone()).into(),
+                *call,
+            ).map_err(|_| Error::<T>::FailedToSchedule)?;
+
+            NextScheduleId::put(next_id);
+            Schedules::<T>::insert(id, (sender.clone(), when));
+            ScheduleDeposits::<T>::insert(id, deposit);
+            ScheduledCount::<T>::insert(&sender, count + 1);
+            Agenda::<T>::append(when, id);
+
+            Self::deposit_event(RawEvent::Scheduled(sender, id, when));
+
+            Ok(())
+        }
+
+        /// Cancel a call the sender scheduled and that didn't run yet.
+        #[weight = 0]
+        pub fn cancel(origin, id: ScheduleId) -> dispatch::DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let (owner, when) = Self::schedules(id).ok_or(Error::<T>::ScheduleNotExist)?;
+            ensure!(owner == sender, Error::<T>::NotScheduleOwner);
+
+            T::Scheduler::cancel_named(Self::task_name(id)).map_err(|_| Error::<T>::ScheduleNotExist)?;
+
+            Schedules::<T>::remove(id);
+            T::Currency::unreserve(&sender, ScheduleDeposits::<T>::take(id));
+            ScheduledCount::<T>::mutate(&sender, |count| *count = count.saturating_sub(1));
+            Agenda::<T>::mutate(when, |ids| ids.retain(|x| *x != id));
+
+            Self::deposit_event(RawEvent::Canceled(sender, id));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    fn task_name(id: ScheduleId) -> Vec<u8> {
+        (TASK_PREFIX, id).encode()
+    }
+
+    /// Free the per-account slots and deposits of the calls dispatched at `now`.
+    fn release(now: T::BlockNumber) -> Weight {
+        let ids = Agenda::<T>::take(now);
+
+        for id in ids.iter() {
+            if let Some((owner, _)) = Schedules::<T>::take(id) {
+                T::Currency::unreserve(&owner, ScheduleDeposits::<T>::take(id));
+                ScheduledCount::<T>::mutate(&owner, |count| *count = count.saturating_sub(1));
+            }
+        }
+
+        let n = ids.len() as Weight;
+        T::DbWeight::get().reads_writes(1 + n * 4, 1 + n * 4)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
+    traits::{Filter, OnInitialize},
+    weights::Weight,
+};
+use frame_system::{self as system, EnsureRoot};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+use balances;
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+mod scheduled_calls {
+    pub use crate::*;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        balances<T>,
+        pallet_scheduler<T>,
+        scheduled_calls<T>,
+    }
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        balances::Balances,
+        pallet_scheduler::Scheduler,
+        scheduled_calls::ScheduledCalls,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1_000_000_000;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const MaxScheduledPerBlock: u32 = 10;
+	pub const MaxScheduledPerAccount: u32 = 2;
+	pub const MaxScheduleDelay: u64 = 10;
+	pub const ScheduleDeposit: u64 = 10;
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for Test {
+    type Balance = u64;
+    type MaxLocks = ();
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<Test>;
+    type WeightInfo = ();
+}
+
+impl pallet_scheduler::Trait for Test {
+    type Event = TestEvent;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumBlockWeight;
+    type ScheduleOrigin = EnsureRoot<u64>;
+    type MaxScheduledPerBlock = MaxScheduledPerBlock;
+    type WeightInfo = ();
+}
+
+/// Only balance transfers may be scheduled.
+pub struct TransfersOnly;
+impl Filter<Call> for TransfersOnly {
+    fn filter(call: &Call) -> bool {
+        matches!(call, Call::Balances(balances::Call::transfer(..)))
+    }
+}
+
+impl Trait for Test {
+    type Event = TestEvent;
+    type ScheduledCall = Call;
+    type CallFilter = TransfersOnly;
+    type PalletsOrigin = OriginCaller;
+    type Scheduler = Scheduler;
+    type MaxScheduledPerAccount = MaxScheduledPerAccount;
+    type MaxScheduleDelay = MaxScheduleDelay;
+    type Currency = Balances;
+    type ScheduleDeposit = ScheduleDeposit;
+}
+
+pub type ScheduledCalls = Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
+pub type Balances = balances::Module<Test>;
+pub type System = frame_system::Module<Test>;
+
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        Scheduler::on_initialize(System::block_number());
+        ScheduledCalls::on_initialize(System::block_number());
+    }
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::from(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,91 @@
+use crate::{mock::*, Error, RawEvent};
+use frame_support::{assert_noop, assert_ok, traits::ReservableCurrency};
+use sp_runtime::DispatchError;
+
+fn transfer(to: u64, value: u64) -> Box<Call> {
+    Box::new(Call::Balances(balances::Call::transfer(to, value)))
+}
+
+#[test]
+fn scheduled_call_runs_signed_by_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 4, transfer(2, 100)));
+        assert_eq!(
+            System::events().pop().expect("an event was deposited").event,
+            TestEvent::scheduled_calls(RawEvent::Scheduled(1, 0, 4))
+        );
+        assert_eq!(ScheduledCalls::scheduled_count(1), 1);
+        assert_eq!(Balances::reserved_balance(1), 10);
+
+        run_to_block(3);
+        assert_eq!(Balances::free_balance(2), 1_000);
+
+        run_to_block(4);
+        assert_eq!(Balances::free_balance(1), 900);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 1_100);
+        assert_eq!(ScheduledCalls::scheduled_count(1), 0);
+        assert_eq!(ScheduledCalls::schedules(0), None);
+    })
+}
+
+#[test]
+fn schedule_checks_filter_and_block() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ScheduledCalls::schedule(Origin::signed(1), 4, Box::new(Call::System(frame_system::Call::remark(vec![])))),
+            Error::<Test>::CallNotAllowed
+        );
+        assert_noop!(ScheduledCalls::schedule(Origin::signed(1), 1, transfer(2, 100)), Error::<Test>::ScheduleInPast);
+        assert_noop!(ScheduledCalls::schedule(Origin::signed(1), 12, transfer(2, 100)), Error::<Test>::ScheduleTooFar);
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 11, transfer(2, 100)));
+        assert_noop!(ScheduledCalls::schedule(Origin::root(), 4, transfer(2, 100)), DispatchError::BadOrigin);
+    })
+}
+
+#[test]
+fn schedule_is_limited_per_account() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 4, transfer(2, 100)));
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 5, transfer(2, 100)));
+        assert_noop!(ScheduledCalls::schedule(Origin::signed(1), 6, transfer(2, 100)), Error::<Test>::TooManyScheduled);
+
+        // other accounts have their own limit
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(2), 6, transfer(1, 100)));
+
+        // slots free up once the calls ran
+        run_to_block(4);
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 6, transfer(2, 100)));
+    })
+}
+
+#[test]
+fn cancel_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ScheduledCalls::schedule(Origin::signed(1), 4, transfer(2, 100)));
+
+        assert_noop!(ScheduledCalls::cancel(Origin::signed(2), 0), Error::<Test>::NotScheduleOwner);
+        assert_noop!(ScheduledCalls::cancel(Origin::signed(1), 1), Error::<Test>::ScheduleNotExist);
+
+        assert_ok!(ScheduledCalls::cancel(Origin::signed(1), 0));
+        assert_eq!(
+            System::events().pop().expect("an event was deposited").event,
+            TestEvent::scheduled_calls(RawEvent::Canceled(1, 0))
+        );
+        assert_eq!(ScheduledCalls::scheduled_count(1), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+
+        run_to_block(4);
+        assert_eq!(Balances::free_balance(2), 1_000);
+    })
+}
+
+#[test]
+fn schedule_needs_deposit() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::reserve(&2, 995);
+
+        assert_noop!(ScheduledCalls::schedule(Origin::signed(2), 4, transfer(1, 1)), Error::<Test>::BalanceNotEnough);
+        assert_eq!(ScheduledCalls::scheduled_count(2), 0);
+    })
+}
@@ -0,0 +1,164 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Emergency switch for pallets and calls.
+//!
+//! `PausedCallFilter` is meant to be the runtime's `BaseCallFilter`. It rejects every call of a
+//! paused pallet and every paused call, until `PauseOrigin` lifts the pause again. Root calls
+//! bypass the base filter, so governance can always dispatch a fix.
+//!
+//! Pallets in `UnpausablePallets`, and this pallet itself, can never be paused: the chain must
+//! keep producing blocks and governance must keep working while anything else is halted.
+
+use frame_support::{decl_module, decl_storage, decl_event, dispatch,
+                    ensure, decl_error, StorageMap, StorageDoubleMap,
+                    traits::{EnsureOrigin, Filter, CallMetadata, Get, GetCallMetadata, PalletInfo},
+};
+use sp_std::marker::PhantomData;
+use sp_std::prelude::*;
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub trait Trait: frame_system::Trait {
+    type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin allowed to pause and unpause pallets and calls.
+    type PauseOrigin: EnsureOrigin<Self::Origin>;
+    /// Names of pallets, as in `construct_runtime!`, whose calls are never paused.
+    type UnpausablePallets: Get<&'static [&'static [u8]]>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as TxPause {
+        // pallet name => every call of the pallet is paused
+        pub PausedPallets get(fn pallet_paused): map hasher(twox_64_concat) Vec<u8> => bool;
+        // (pallet name, call name) => the call is paused
+        pub PausedCalls get(fn call_paused): double_map hasher(twox_64_concat) Vec<u8>, hasher(twox_64_concat) Vec<u8> => bool;
+    }
+}
+
+decl_event!(
+    pub enum Event {
+        /// Every call of a pallet is paused. (pallet_name)
+        PalletPaused(Vec<u8>),
+        /// A pallet is unpaused. (pallet_name)
+        PalletUnpaused(Vec<u8>),
+        /// A single call is paused. (pallet_name, call_name)
+        CallPaused(Vec<u8>, Vec<u8>),
+        /// A single call is unpaused. (pallet_name, call_name)
+        CallUnpaused(Vec<u8>, Vec<u8>),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// This pallet can't pause itself, nobody but root could lift the pause.
+        CannotPauseSelf,
+        /// The pallet is listed in `UnpausablePallets`.
+        Unpausable,
+        AlreadyPaused,
+        NotPaused,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Errors must be initialized if they are used by the pallet.
+        type Error = Error<T>;
+
+        // Events must be initialized if they are used by the pallet.
+        fn deposit_event() = default;
+
+        /// Pause every call of `pallet_name`, as named in `construct_runtime!`.
+        #[weight = 0]
+        pub fn pause_pallet(origin, pallet_name: Vec<u8>) -> dispatch::DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            ensure!(!Self::is_self(&pallet_name), Error::<T>::CannotPauseSelf);
+            ensure!(!Self::is_unpausable(&pallet_name), Error::<T>::Unpausable);
+            ensure!(!Self::pallet_paused(&pallet_name), Error::<T>::AlreadyPaused);
+
+            PausedPallets::insert(&pallet_name, true);
+            Self::deposit_event(Event::PalletPaused(pallet_name));
+
+            Ok(())
+        }
+
+        /// Lift a pause set by `pause_pallet`. Calls paused one by one stay paused.
+        #[weight = 0]
+        pub fn unpause_pallet(origin, pallet_name: Vec<u8>) -> dispatch::DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            ensure!(Self::pallet_paused(&pallet_name), Error::<T>::NotPaused);
+
+            PausedPallets::remove(&pallet_name);
+            Self::deposit_event(Event::PalletUnpaused(pallet_name));
+
+            Ok(())
+        }
+
+        /// Pause a single call, e.g. `(b"KittiesModule", b"buy")`.
+        #[weight = 0]
+        pub fn pause_call(origin, pallet_name: Vec<u8>, call_name: Vec<u8>) -> dispatch::DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            ensure!(!Self::is_self(&pallet_name), Error::<T>::CannotPauseSelf);
+            ensure!(!Self::is_unpausable(&pallet_name), Error::<T>::Unpausable);
+            ensure!(!Self::call_paused(&pallet_name, &call_name), Error::<T>::AlreadyPaused);
+
+            PausedCalls::insert(&pallet_name, &call_name, true);
+            Self::deposit_event(Event::CallPaused(pallet_name, call_name));
+
+            Ok(())
+        }
+
+        /// Lift a pause set by `pause_call`.
+        #[weight = 0]
+        pub fn unpause_call(origin, pallet_name: Vec<u8>, call_name: Vec<u8>) -> dispatch::DispatchResult {
+            T::PauseOrigin::ensure_origin(origin)?;
+
+            ensure!(Self::call_paused(&pallet_name, &call_name), Error::<T>::NotPaused);
+
+            PausedCalls::remove(&pallet_name, &call_name);
+            Self::deposit_event(Event::CallUnpaused(pallet_name, call_name));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Whether `function_name` of `pallet_name` is currently rejected.
+    ///
+    /// Unpausable pallets are never rejected, even if a pause was stored before they were listed.
+    pub fn is_paused(pallet_name: &[u8], function_name: &[u8]) -> bool {
+        if Self::is_self(pallet_name) || Self::is_unpausable(pallet_name) {
+            return false;
+        }
+        PausedPallets::get(pallet_name) || PausedCalls::get(pallet_name, function_name)
+    }
+
+    fn is_unpausable(pallet_name: &[u8]) -> bool {
+        T::UnpausablePallets::get().iter().any(|name| *name == pallet_name)
+    }
+
+    fn is_self(pallet_name: &[u8]) -> bool {
+        <T as frame_system::Trait>::PalletInfo::name::<Self>()
+            .map_or(false, |name| name.as_bytes() == pallet_name)
+    }
+}
+
+/// Rejects calls paused through this pallet. Use it as `BaseCallFilter`.
+pub struct PausedCallFilter<T>(PhantomData<T>);
+
+impl<T: Trait> Filter<<T as frame_system::Trait>::Call> for PausedCallFilter<T> where
+    <T as frame_system::Trait>::Call: GetCallMetadata,
+{
+    fn filter(call: &<T as frame_system::Trait>::Call) -> bool {
+        let CallMetadata { pallet_name, function_name } = call.get_call_metadata();
+        !Module::<T>::is_paused(pallet_name.as_bytes(), function_name.as_bytes())
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{Module, Trait};
+use frame_support::{
+    impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
+    weights::Weight,
+};
+use frame_system as system;
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Perbill,
+};
+
+use balances;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+mod tx_pause {
+    pub use crate::*;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        tx_pause,
+        balances<T>,
+    }
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        frame_system::System,
+        balances::Balances,
+        tx_pause::TxPause,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	pub const UnpausablePallets: &'static [&'static [u8]] = &[b"System"];
+}
+
+/// Names pallets the way `construct_runtime!` would.
+pub struct PalletInfo;
+impl frame_support::traits::PalletInfo for PalletInfo {
+    fn index<P: 'static>() -> Option<usize> {
+        None
+    }
+
+    fn name<P: 'static>() -> Option<&'static str> {
+        let type_id = sp_std::any::TypeId::of::<P>();
+        if type_id == sp_std::any::TypeId::of::<TxPause>() {
+            return Some("TxPause");
+        }
+        if type_id == sp_std::any::TypeId::of::<Balances>() {
+            return Some("Balances");
+        }
+        if type_id == sp_std::any::TypeId::of::<System>() {
+            return Some("System");
+        }
+        None
+    }
+}
+
+impl system::Trait for Test {
+    type BaseCallFilter = crate::PausedCallFilter<Test>;
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = TestEvent;
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+impl balances::Trait for Test {
+    type Balance = u64;
+    type MaxLocks = ();
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = system::Module<Test>;
+    type WeightInfo = ();
+}
+
+impl Trait for Test {
+    type Event = TestEvent;
+    type PauseOrigin = system::EnsureRoot<u64>;
+    type UnpausablePallets = UnpausablePallets;
+}
+
+pub type TxPause = Module<Test>;
+pub type Balances = balances::Module<Test>;
+pub type System = frame_system::Module<Test>;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1_000), (2, 1_000)],
+	}.assimilate_storage(&mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::from(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,119 @@
+use crate::{mock::*, Error, Event, PausedCallFilter};
+use frame_support::{assert_noop, assert_ok, traits::Filter, StorageDoubleMap, StorageMap};
+use sp_runtime::traits::Dispatchable;
+use sp_runtime::DispatchError;
+
+fn transfer_call() -> Call {
+    Call::Balances(balances::Call::transfer(2, 10))
+}
+
+fn last_event() -> TestEvent {
+    System::events().pop().expect("an event was deposited").event
+}
+
+#[test]
+fn pause_call_filters_only_that_call() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TxPause::pause_call(Origin::root(), b"Balances".to_vec(), b"transfer".to_vec()));
+        assert_eq!(
+            last_event(),
+            TestEvent::tx_pause(Event::CallPaused(b"Balances".to_vec(), b"transfer".to_vec()))
+        );
+
+        assert!(!PausedCallFilter::<Test>::filter(&transfer_call()));
+        assert!(PausedCallFilter::<Test>::filter(&Call::Balances(balances::Call::transfer_keep_alive(2, 10))));
+        assert_noop!(transfer_call().dispatch(Origin::signed(1)), DispatchError::BadOrigin);
+
+        assert_ok!(TxPause::unpause_call(Origin::root(), b"Balances".to_vec(), b"transfer".to_vec()));
+        assert!(PausedCallFilter::<Test>::filter(&transfer_call()));
+        assert_ok!(transfer_call().dispatch(Origin::signed(1)));
+        assert_eq!(Balances::free_balance(2), 1_010);
+    })
+}
+
+#[test]
+fn pause_pallet_filters_all_its_calls() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TxPause::pause_pallet(Origin::root(), b"Balances".to_vec()));
+        assert_eq!(last_event(), TestEvent::tx_pause(Event::PalletPaused(b"Balances".to_vec())));
+
+        assert!(!PausedCallFilter::<Test>::filter(&transfer_call()));
+        assert!(!PausedCallFilter::<Test>::filter(&Call::Balances(balances::Call::transfer_keep_alive(2, 10))));
+        assert!(PausedCallFilter::<Test>::filter(&Call::System(frame_system::Call::remark(vec![]))));
+
+        assert_ok!(TxPause::unpause_pallet(Origin::root(), b"Balances".to_vec()));
+        assert_eq!(last_event(), TestEvent::tx_pause(Event::PalletUnpaused(b"Balances".to_vec())));
+        assert!(PausedCallFilter::<Test>::filter(&transfer_call()));
+    })
+}
+
+#[test]
+fn pause_requires_pause_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(TxPause::pause_pallet(Origin::signed(1), b"Balances".to_vec()), DispatchError::BadOrigin);
+        assert_noop!(
+            TxPause::pause_call(Origin::signed(1), b"Balances".to_vec(), b"transfer".to_vec()),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(TxPause::pause_pallet(Origin::root(), b"Balances".to_vec()));
+        assert_noop!(TxPause::unpause_pallet(Origin::signed(1), b"Balances".to_vec()), DispatchError::BadOrigin);
+    })
+}
+
+#[test]
+fn cannot_pause_self() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(TxPause::pause_pallet(Origin::root(), b"TxPause".to_vec()), Error::<Test>::CannotPauseSelf);
+        assert_noop!(
+            TxPause::pause_call(Origin::root(), b"TxPause".to_vec(), b"unpause_pallet".to_vec()),
+            Error::<Test>::CannotPauseSelf
+        );
+    })
+}
+
+#[test]
+fn cannot_pause_unpausable_pallet() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(TxPause::pause_pallet(Origin::root(), b"System".to_vec()), Error::<Test>::Unpausable);
+        assert_noop!(
+            TxPause::pause_call(Origin::root(), b"System".to_vec(), b"remark".to_vec()),
+            Error::<Test>::Unpausable
+        );
+    })
+}
+
+#[test]
+fn stored_pause_of_unpausable_pallet_is_ignored() {
+    new_test_ext().execute_with(|| {
+        crate::PausedPallets::insert(b"System".to_vec(), true);
+        crate::PausedCalls::insert(b"TxPause".to_vec(), b"unpause_pallet".to_vec(), true);
+
+        assert!(PausedCallFilter::<Test>::filter(&Call::System(frame_system::Call::remark(vec![]))));
+        assert!(PausedCallFilter::<Test>::filter(&Call::TxPause(crate::Call::unpause_pallet(b"Balances".to_vec()))));
+    })
+}
+
+#[test]
+fn pause_twice_or_unpause_unpaused_fails() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(TxPause::unpause_pallet(Origin::root(), b"Balances".to_vec()), Error::<Test>::NotPaused);
+        assert_noop!(
+            TxPause::unpause_call(Origin::root(), b"Balances".to_vec(), b"transfer".to_vec()),
+            Error::<Test>::NotPaused
+        );
+
+        assert_ok!(TxPause::pause_pallet(Origin::root(), b"Balances".to_vec()));
+        assert_noop!(TxPause::pause_pallet(Origin::root(), b"Balances".to_vec()), Error::<Test>::AlreadyPaused);
+    })
+}
+
+#[test]
+fn root_bypasses_pause() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TxPause::pause_pallet(Origin::root(), b"Balances".to_vec()));
+
+        assert_ok!(Call::Balances(balances::Call::set_balance(3, 500, 0)).dispatch(Origin::root()));
+        assert_eq!(Balances::free_balance(3), 500);
+    })
+}
@@ -0,0 +1,177 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Proof-of-authority validator set for `pallet_session`.
+//!
+//! `ValidatorOrigin` adds and removes validators, and validators reported through
+//! `pallet_offences` are removed automatically. The module is the session manager of
+//! `pallet_session::historical`, which keeps the roots key ownership proofs are checked against.
+//!
+//! A validator is added only once it registered keys with `Session::set_keys`, e.g. the result
+//! of `author_rotateKeys`, and is left out of sessions while it has none. The set is handed to
+//! the session pallet at the next session change and is active from the one after.
+
+use codec::Encode;
+use frame_support::{decl_module, decl_storage, decl_event, dispatch,
+                    ensure, decl_error, StorageValue, StorageHasher, Twox64Concat,
+                    storage::migration::have_storage_value,
+                    traits::{Get, EnsureOrigin},
+                    weights::Weight,
+};
+use sp_runtime::{traits::Convert, Perbill};
+use sp_staking::{offence::{OffenceDetails, OnOffenceHandler}, SessionIndex};
+use sp_std::marker::PhantomData;
+use sp_std::prelude::*;
+
+// import test file
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub trait Trait: pallet_session::Trait {
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Origin allowed to add and remove validators.
+    type ValidatorOrigin: EnsureOrigin<Self::Origin>;
+    /// The set never shrinks below this, neither by `remove_validator` nor by offences.
+    type MinValidators: Get<u32>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as ValidatorSet {
+        pub Validators get(fn validators) config(): Vec<T::ValidatorId>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T> where ValidatorId = <T as pallet_session::Trait>::ValidatorId {
+        /// A validator is added to the set. (validator)
+        ValidatorAdded(ValidatorId),
+        /// A validator is removed from the set. (validator)
+        ValidatorRemoved(ValidatorId),
+        /// A validator is removed from the set for an offence. (validator)
+        OffenderRemoved(ValidatorId),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        AlreadyValidator,
+        NotValidator,
+        TooFewValidators,
+        /// The validator has no session keys registered.
+        NoSessionKeys,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        // Errors must be initialized if they are used by the pallet.
+        type Error = Error<T>;
+
+        // Events must be initialized if they are used by the pallet.
+        fn deposit_event() = default;
+
+        const MinValidators: u32 = T::MinValidators::get();
+
+        #[weight = 0]
+        pub fn add_validator(origin, who: T::ValidatorId) -> dispatch::DispatchResult {
+            T::ValidatorOrigin::ensure_origin(origin)?;
+            ensure!(Self::has_session_keys(&who), Error::<T>::NoSessionKeys);
+
+            Validators::<T>::try_mutate(|validators| -> dispatch::DispatchResult {
+                ensure!(!validators.contains(&who), Error::<T>::AlreadyValidator);
+                validators.push(who.clone());
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::ValidatorAdded(who));
+
+            Ok(())
+        }
+
+        #[weight = 0]
+        pub fn remove_validator(origin, who: T::ValidatorId) -> dispatch::DispatchResult {
+            T::ValidatorOrigin::ensure_origin(origin)?;
+
+            Validators::<T>::try_mutate(|validators| -> dispatch::DispatchResult {
+                let index = validators.iter().position(|v| *v == who).ok_or(Error::<T>::NotValidator)?;
+                ensure!(validators.len() as u32 > T::MinValidators::get(), Error::<T>::TooFewValidators);
+                validators.remove(index);
+                Ok(())
+            })?;
+
+            Self::deposit_event(RawEvent::ValidatorRemoved(who));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Whether `who` has keys for the next session. `pallet_session` doesn't expose them and
+    /// would give a validator without any default keys.
+    fn has_session_keys(who: &T::ValidatorId) -> bool {
+        have_storage_value(b"Session", b"NextKeys", &Twox64Concat::hash(&who.encode()))
+    }
+}
+
+impl<T: Trait> pallet_session::historical::SessionManager<T::ValidatorId, ()> for Module<T> {
+    /// The validators with session keys, or no change if none has any.
+    fn new_session(_new_index: SessionIndex) -> Option<Vec<(T::ValidatorId, ())>> {
+        let validators: Vec<_> = Self::validators().into_iter()
+            .filter(Self::has_session_keys)
+            .map(|validator| (validator, ()))
+            .collect();
+
+        if validators.is_empty() {
+            None
+        } else {
+            Some(validators)
+        }
+    }
+
+    fn start_session(_start_index: SessionIndex) {}
+
+    fn end_session(_end_index: SessionIndex) {}
+}
+
+/// Validators have no identification besides their account, e.g. no stake exposure.
+/// Use it as `pallet_session::historical`'s `FullIdentificationOf`.
+pub struct FullIdentificationOf<T>(PhantomData<T>);
+
+impl<T: Trait> Convert<T::ValidatorId, Option<()>> for FullIdentificationOf<T> {
+    fn convert(_validator: T::ValidatorId) -> Option<()> {
+        Some(())
+    }
+}
+
+impl<T: Trait> OnOffenceHandler<T::AccountId, (T::ValidatorId, ()), Weight> for Module<T> {
+    /// Remove the offenders from the set, as long as `MinValidators` remain.
+    fn on_offence(
+        offenders: &[OffenceDetails<T::AccountId, (T::ValidatorId, ())>],
+        _slash_fraction: &[Perbill],
+        _session: SessionIndex,
+    ) -> Result<Weight, ()> {
+        let mut validators = Self::validators();
+
+        for details in offenders {
+            let (offender, _) = &details.offender;
+            if validators.len() as u32 <= T::MinValidators::get() {
+                break;
+            }
+            if let Some(index) = validators.iter().position(|v| v == offender) {
+                validators.remove(index);
+                Self::deposit_event(RawEvent::OffenderRemoved(offender.clone()));
+            }
+        }
+
+        Validators::<T>::put(validators);
+
+        Ok(T::DbWeight::get().reads_writes(1, 1))
+    }
+
+    fn can_report() -> bool {
+        true
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{mock::*, Error, RawEvent};
+use frame_support::{assert_noop, assert_ok};
+use pallet_session::historical::SessionManager;
+use sp_runtime::{testing::UintAuthorityId, DispatchError, Perbill};
+use sp_staking::offence::{OffenceDetails, OnOffenceHandler};
+
+fn last_event() -> TestEvent {
+    System::events().pop().expect("an event was deposited").event
+}
+
+fn offence(offender: u64) -> OffenceDetails<u64, (u64, ())> {
+    OffenceDetails { offender: (offender, ()), reporters: vec![] }
+}
+
+#[test]
+fn add_and_remove_validators() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+        assert_eq!(last_event(), TestEvent::validator_set(RawEvent::ValidatorAdded(4)));
+        assert_eq!(ValidatorSet::validators(), vec![1, 2, 3, 4]);
+        assert_noop!(ValidatorSet::add_validator(Origin::root(), 4), Error::<Test>::AlreadyValidator);
+
+        assert_ok!(ValidatorSet::remove_validator(Origin::root(), 2));
+        assert_eq!(last_event(), TestEvent::validator_set(RawEvent::ValidatorRemoved(2)));
+        assert_eq!(ValidatorSet::validators(), vec![1, 3, 4]);
+        assert_noop!(ValidatorSet::remove_validator(Origin::root(), 2), Error::<Test>::NotValidator);
+    })
+}
+
+#[test]
+fn changes_require_validator_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 4), DispatchError::BadOrigin);
+        assert_noop!(ValidatorSet::remove_validator(Origin::signed(1), 2), DispatchError::BadOrigin);
+    })
+}
+
+#[test]
+fn validators_need_session_keys() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(ValidatorSet::add_validator(Origin::root(), 5), Error::<Test>::NoSessionKeys);
+
+        assert_ok!(Session::set_keys(Origin::signed(5), UintAuthorityId(5), vec![]));
+        assert_ok!(ValidatorSet::add_validator(Origin::root(), 5));
+    })
+}
+
+#[test]
+fn set_keeps_min_validators() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::remove_validator(Origin::root(), 3));
+        assert_noop!(ValidatorSet::remove_validator(Origin::root(), 2), Error::<Test>::TooFewValidators);
+    })
+}
+
+#[test]
+fn offenders_are_removed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+
+        assert_ok!(ValidatorSet::on_offence(&[offence(2)], &[Perbill::from_percent(10)], 0));
+        assert_eq!(last_event(), TestEvent::validator_set(RawEvent::OffenderRemoved(2)));
+        assert_eq!(ValidatorSet::validators(), vec![1, 3, 4]);
+
+        // only down to `MinValidators`
+        assert_ok!(ValidatorSet::on_offence(
+            &[offence(1), offence(3)],
+            &[Perbill::from_percent(10), Perbill::from_percent(10)],
+            0,
+        ));
+        assert_eq!(ValidatorSet::validators(), vec![3, 4]);
+    })
+}
+
+#[test]
+fn new_session_returns_the_set() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(ValidatorSet::new_session(1), Some(vec![(1, ()), (2, ()), (3, ())]));
+
+        assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+        assert_eq!(ValidatorSet::new_session(2), Some(vec![(2, ()), (3, ())]));
+    })
+}
+
+#[test]
+fn new_session_skips_validators_without_keys() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Session::purge_keys(Origin::signed(2)));
+        assert_eq!(ValidatorSet::new_session(1), Some(vec![(1, ()), (3, ())]));
+
+        assert_ok!(Session::purge_keys(Origin::signed(1)));
+        assert_ok!(Session::purge_keys(Origin::signed(3)));
+        assert_eq!(ValidatorSet::new_session(2), None);
+    })
+}
@@ -0,0 +1,102 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Verifies storage proofs of kitty owners and PoE claims.
+//!
+//! A full node serves the proofs over the `proof_kittyOwner` and `proof_claim` RPCs. Checked
+//! here against the state root of a header the caller trusts, e.g. one a light client saw
+//! finalized by GRANDPA, they show what the chain stored without trusting the node that served
+//! them. The crate only needs `no_std` hashing, so it also builds for runtimes and browsers.
+
+use codec::{Decode, Encode};
+use hash256_std_hasher::Hash256StdHasher;
+use sp_core::{hashing::{blake2_128, blake2_256, twox_128}, H256};
+use sp_std::prelude::*;
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+#[cfg(test)]
+mod tests;
+
+/// Storage prefix of the kitties pallet.
+const KITTIES_PREFIX: &[u8] = b"Kitties";
+/// Storage prefix of the PoE pallet.
+const POE_PREFIX: &[u8] = b"TemplateModule";
+
+/// The runtime's `KittyIndex`. Keys of other widths address entries that don't exist.
+pub type KittyIndex = u64;
+
+/// Why a proof was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+	/// The proof doesn't contain the trie nodes from the state root to the key.
+	InvalidProof,
+	/// The proven value isn't of the expected type.
+	InvalidValue,
+}
+
+/// BLAKE2b-256, the hasher of the state trie, without the host functions `sp-io` needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Blake2Hasher;
+
+impl hash_db::Hasher for Blake2Hasher {
+	type Out = H256;
+	type StdHasher = Hash256StdHasher;
+	const LENGTH: usize = 32;
+
+	fn hash(s: &[u8]) -> H256 {
+		blake2_256(s).into()
+	}
+}
+
+/// Storage key of a `blake2_128_concat` map entry.
+fn map_key(prefix: &[u8], item: &[u8], key: &[u8]) -> Vec<u8> {
+	[&twox_128(prefix)[..], &twox_128(item)[..], &blake2_128(key)[..], key].concat()
+}
+
+/// Storage key of the owner of `kitty_id`.
+pub fn kitty_owner_key(kitty_id: KittyIndex) -> Vec<u8> {
+	map_key(KITTIES_PREFIX, b"KittyOwners", &kitty_id.encode())
+}
+
+/// Storage key of the proof of `claim`.
+pub fn claim_key(claim: &[u8]) -> Vec<u8> {
+	map_key(POE_PREFIX, b"Proofs", &claim.encode())
+}
+
+/// The value `proof` shows is stored under `key` in the state with `state_root`, `None` if it
+/// shows nothing is.
+pub fn verify_read_proof(
+	state_root: &H256,
+	key: &[u8],
+	proof: Vec<Vec<u8>>,
+) -> Result<Option<Vec<u8>>, Error> {
+	let db = StorageProof::new(proof).into_memory_db::<Blake2Hasher>();
+
+	read_trie_value::<Layout<Blake2Hasher>, _>(&db, state_root, key).map_err(|_| Error::InvalidProof)
+}
+
+fn verify_value<V: Decode>(state_root: &H256, key: &[u8], proof: Vec<Vec<u8>>) -> Result<Option<V>, Error> {
+	verify_read_proof(state_root, key, proof)?
+		.map(|value| V::decode(&mut &value[..]).map_err(|_| Error::InvalidValue))
+		.transpose()
+}
+
+/// The owner of `kitty_id` according to `proof`, `None` if the kitty doesn't exist.
+pub fn verify_kitty_owner<AccountId: Decode>(
+	state_root: &H256,
+	kitty_id: KittyIndex,
+	proof: Vec<Vec<u8>>,
+) -> Result<Option<AccountId>, Error> {
+	verify_value(state_root, &kitty_owner_key(kitty_id), proof)
+}
+
+/// The proof of `claim` according to `proof`, `None` if nobody holds the claim.
+///
+/// `Proof` is the PoE pallet's `Proof<AccountId, BlockNumber>`, or any type that decodes the
+/// same, e.g. `(AccountId, BlockNumber, Option<BlockNumber>)`.
+pub fn verify_claim<Proof: Decode>(
+	state_root: &H256,
+	claim: &[u8],
+	proof: Vec<Vec<u8>>,
+) -> Result<Option<Proof>, Error> {
+	verify_value(state_root, &claim_key(claim), proof)
+}
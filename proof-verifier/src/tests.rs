@@ -0,0 +1,53 @@
+use crate::*;
+use sp_core::storage::Storage;
+use sp_state_machine::{prove_read, Backend, InMemoryBackend};
+
+type ClaimProof = (u64, u32, Option<u32>);
+
+/// Kitty 1 owned by account 7, and claim `hello` held by account 7 since block 3.
+fn state() -> InMemoryBackend<Blake2Hasher> {
+	let mut storage = Storage::default();
+	storage.top.insert(kitty_owner_key(1), 7u64.encode());
+	storage.top.insert(claim_key(b"hello"), (7u64, 3u32, None::<u32>).encode());
+	storage.into()
+}
+
+/// The state root and a read proof of `key`.
+fn prove(key: &[u8]) -> (H256, Vec<Vec<u8>>) {
+	let root = state().storage_root(sp_std::iter::empty()).0;
+	let proof = prove_read(state(), &[key]).expect("keys can be proven");
+	(root, proof.iter_nodes().collect())
+}
+
+#[test]
+fn kitty_owner_is_proven() {
+	let (root, proof) = prove(&kitty_owner_key(1));
+	assert_eq!(verify_kitty_owner::<u64>(&root, 1, proof), Ok(Some(7)));
+
+	let (root, proof) = prove(&kitty_owner_key(2));
+	assert_eq!(verify_kitty_owner::<u64>(&root, 2, proof), Ok(None));
+}
+
+#[test]
+fn claim_is_proven() {
+	let (root, proof) = prove(&claim_key(b"hello"));
+	assert_eq!(verify_claim::<ClaimProof>(&root, b"hello", proof), Ok(Some((7, 3, None))));
+
+	let (root, proof) = prove(&claim_key(b"world"));
+	assert_eq!(verify_claim::<ClaimProof>(&root, b"world", proof), Ok(None));
+}
+
+#[test]
+fn proof_must_match_state_root() {
+	let (_, proof) = prove(&kitty_owner_key(1));
+	assert_eq!(verify_kitty_owner::<u64>(&H256::repeat_byte(1), 1, proof), Err(Error::InvalidProof));
+
+	let (root, _) = prove(&kitty_owner_key(1));
+	assert_eq!(verify_kitty_owner::<u64>(&root, 1, vec![]), Err(Error::InvalidProof));
+}
+
+#[test]
+fn value_must_decode() {
+	let (root, proof) = prove(&kitty_owner_key(1));
+	assert_eq!(verify_kitty_owner::<[u8; 32]>(&root, 1, proof), Err(Error::InvalidValue));
+}
@@ -7,19 +7,27 @@
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use sp_std::prelude::*;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{
+	crypto::KeyTypeId, OpaqueMetadata,
+	u32_trait::{_1, _2, _3, _4, _5},
+};
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
-	transaction_validity::{TransactionValidity, TransactionSource},
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
+	RuntimeDebug, transaction_validity::{TransactionValidity, TransactionSource},
 };
+use codec::{Encode, Decode};
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, IdentityLookup, Verify, IdentifyAccount, NumberFor, Saturating,
+	BlakeTwo256, Block as BlockT, StaticLookup, Verify, IdentifyAccount, NumberFor, Saturating,
+	ConvertInto, OpaqueKeys,
 };
 use sp_api::impl_runtime_apis;
+#[cfg(not(feature = "consensus-babe"))]
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use pallet_grandpa::{AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList};
 use pallet_grandpa::fg_primitives;
 use sp_version::RuntimeVersion;
+use frame_system::{EnsureRoot, EnsureOneOf};
+use pallet_session::historical as pallet_session_historical;
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 
@@ -28,18 +36,32 @@ use sp_version::NativeVersion;
 pub use sp_runtime::BuildStorage;
 pub use pallet_timestamp::Call as TimestampCall;
 pub use pallet_balances::Call as BalancesCall;
-pub use sp_runtime::{Permill, Perbill};
+pub use sp_runtime::{Permill, Perbill, Percent};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{
+		KeyOwnerProofSystem, Randomness, Currency, Imbalance, OnUnbalanced, FindAuthor,
+		Contains, ContainsLengthBound, InstanceFilter, Filter,
+	},
 	weights::{
-		Weight, IdentityFee,
+		Weight, WeightToFeePolynomial, WeightToFeeCoefficient, WeightToFeeCoefficients,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 	},
 };
+use pallet_transaction_payment::{TargetedFeeAdjustment, Multiplier};
+use sp_runtime::{FixedPointNumber, Perquintill};
+use smallvec::smallvec;
 
 pub use pallet_poe;
 pub use pallet_kitties;
+pub use pallet_kitty_arena;
+pub use pallet_kitty_oracle;
+pub use pallet_tx_pause;
+#[cfg(feature = "npos")]
+pub use pallet_staking::StakerStatus;
+
+#[cfg(test)]
+mod tests;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -68,7 +90,7 @@ pub type Hash = sp_core::H256;
 pub type DigestItem = generic::DigestItem<Hash>;
 
 /// Kitty index type
-pub type KittyInex = u32;
+pub type KittyIndex = u64;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -86,33 +108,57 @@ pub mod opaque {
 	/// Opaque block identifier type.
 	pub type BlockId = generic::BlockId<Block>;
 
+	#[cfg(not(feature = "consensus-babe"))]
 	impl_opaque_keys! {
 		pub struct SessionKeys {
 			pub aura: Aura,
 			pub grandpa: Grandpa,
 		}
 	}
+
+	#[cfg(feature = "consensus-babe")]
+	impl_opaque_keys! {
+		pub struct SessionKeys {
+			pub babe: Babe,
+			pub grandpa: Grandpa,
+		}
+	}
 }
 
 pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	// `setCode` only takes a higher version, so `no-sudo` builds stay one ahead. Bump both together.
+	spec_version: if cfg!(feature = "no-sudo") { 40 } else { 39 },
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// `Sudo` keeps its slot in `no-sudo` builds, those already went out with 7
+	transaction_version: 7,
 };
 
+/// Block time of chains whose genesis doesn't set `BlockTime::slot_duration`.
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
 
 pub const SLOT_DURATION: u64 = MILLISECS_PER_BLOCK;
 
-// Time is measured by number of blocks.
+// Time is measured by number of blocks. These assume the default block time, not the genesis
+// `BlockTime::slot_duration`: with 1 second slots every period below passes 6 times faster.
 pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Balance units. The token has 12 decimals. A base extrinsic of 100 bytes costs 1.1 CENTS,
+// deposits stay well above that so storage can't be filled for the price of the fees.
+pub const DOLLARS: Balance = 1_000_000_000_000;
+pub const CENTS: Balance = DOLLARS / 100;
+pub const MILLICENTS: Balance = CENTS / 1_000;
+
+/// Deposit for storing `items` entries taking `bytes` bytes in total.
+pub const fn deposit(items: u32, bytes: u32) -> Balance {
+	items as Balance * 20 * CENTS + (bytes as Balance) * 10 * MILLICENTS
+}
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -137,14 +183,18 @@ parameter_types! {
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Trait for Runtime {
-	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = ();
+	/// The basic call filter to use in dispatchable. Rejects calls paused through `TxPause`.
+	///
+	/// Calls dispatched through proxies and multisigs pass this filter too, proxies
+	/// then apply their `ProxyType` filter on top.
+	type BaseCallFilter = BaseFilter;
 	/// The identifier used to distinguish between accounts.
 	type AccountId = AccountId;
 	/// The aggregated dispatch type that is available for extrinsics.
 	type Call = Call;
 	/// The lookup mechanism to get account ID from whatever is passed in dispatchers.
-	type Lookup = IdentityLookup<AccountId>;
+	/// Accepts either a full account ID or a short account index.
+	type Lookup = Indices;
 	/// The index type for storing how many extrinsics an account has signed.
 	type Index = Index;
 	/// The index type for blocks.
@@ -195,15 +245,59 @@ impl frame_system::Trait for Runtime {
 	type SystemWeightInfo = ();
 }
 
+/// The pallet assigning slots to block authors, Aura or with `consensus-babe` BABE.
+#[cfg(not(feature = "consensus-babe"))]
+type Consensus = Aura;
+/// The pallet assigning slots to block authors, Aura or with `consensus-babe` BABE.
+#[cfg(feature = "consensus-babe")]
+type Consensus = Babe;
+
+#[cfg(not(feature = "consensus-babe"))]
 impl pallet_aura::Trait for Runtime {
 	type AuthorityId = AuraId;
 }
 
+/// Slots in a BABE epoch, which is as long as a session.
+#[cfg(feature = "consensus-babe")]
+pub const EPOCH_DURATION_IN_SLOTS: u64 = HOURS as u64;
+
+/// The probability of a slot having a primary author, the other slots get a secondary one.
+#[cfg(feature = "consensus-babe")]
+pub const PRIMARY_PROBABILITY: (u64, u64) = (1, 4);
+
+#[cfg(feature = "consensus-babe")]
+parameter_types! {
+	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
+}
+
+// Epochs change along with sessions, and BABE equivocations are reported like GRANDPA's.
+#[cfg(feature = "consensus-babe")]
+impl pallet_babe::Trait for Runtime {
+	type EpochDuration = EpochDuration;
+	/// The slot duration set at genesis, like the timestamp pallet's `MinimumPeriod`.
+	type ExpectedBlockTime = pallet_block_time::ExpectedBlockTime<Runtime>;
+	type EpochChangeTrigger = pallet_babe::ExternalTrigger;
+
+	type KeyOwnerProofSystem = Historical;
+
+	type KeyOwnerProof =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, pallet_babe::AuthorityId)>>::Proof;
+
+	type KeyOwnerIdentification = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		pallet_babe::AuthorityId,
+	)>>::IdentificationTuple;
+
+	type HandleEquivocation = pallet_babe::EquivocationHandler<Self::KeyOwnerIdentification, Offences>;
+
+	type WeightInfo = ();
+}
+
 impl pallet_grandpa::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -213,25 +307,260 @@ impl pallet_grandpa::Trait for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation = pallet_grandpa::EquivocationHandler<Self::KeyOwnerIdentification, Offences>;
+
+	type WeightInfo = ();
+}
 
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = 1 * HOURS;
+	pub const SessionOffset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(17);
+	pub const MinValidators: u32 = 1;
+	pub OffencesWeightSoftLimit: Weight = Perbill::from_percent(60) * MaximumBlockWeight::get();
+}
+
+// Sessions rotate the Aura (or BABE) and GRANDPA authorities to `ValidatorSet`, or to the
+// validators `Staking` elected with `npos`, and let `pallet_session_historical` prove which
+// validator owned a key in a past session. With BABE a session is an epoch.
+impl pallet_session::Trait for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	#[cfg(not(feature = "npos"))]
+	type ValidatorIdOf = ConvertInto;
+	#[cfg(feature = "npos")]
+	type ValidatorIdOf = pallet_staking::StashOf<Self>;
+	#[cfg(not(feature = "consensus-babe"))]
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	#[cfg(feature = "consensus-babe")]
+	type ShouldEndSession = Babe;
+	#[cfg(not(feature = "consensus-babe"))]
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	#[cfg(feature = "consensus-babe")]
+	type NextSessionRotation = Babe;
+	#[cfg(not(feature = "npos"))]
+	type SessionManager = pallet_session_historical::NoteHistoricalRoot<Self, ValidatorSet>;
+	#[cfg(feature = "npos")]
+	type SessionManager = pallet_session_historical::NoteHistoricalRoot<Self, Staking>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
 	type WeightInfo = ();
 }
 
+#[cfg(not(feature = "npos"))]
+impl pallet_session_historical::Trait for Runtime {
+	type FullIdentification = ();
+	type FullIdentificationOf = pallet_validator_set::FullIdentificationOf<Runtime>;
+}
+
+#[cfg(feature = "npos")]
+impl pallet_session_historical::Trait for Runtime {
+	type FullIdentification = pallet_staking::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
+}
+
+#[cfg(not(feature = "npos"))]
+impl pallet_validator_set::Trait for Runtime {
+	type Event = Event;
+	type ValidatorOrigin = EnsureRootOrHalfCouncil;
+	type MinValidators = MinValidators;
+}
+
+/// Offenders, e.g. GRANDPA equivocators, are dropped from `ValidatorSet`, or slashed with `npos`.
+impl pallet_offences::Trait for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session_historical::IdentificationTuple<Self>;
+	#[cfg(not(feature = "npos"))]
+	type OnOffenceHandler = ValidatorSet;
+	#[cfg(feature = "npos")]
+	type OnOffenceHandler = Staking;
+	type WeightSoftLimit = OffencesWeightSoftLimit;
+}
+
+#[cfg(feature = "npos")]
+parameter_types! {
+	pub const UncleGenerations: BlockNumber = 0;
+}
+
+/// Credits block authors with era points, which `Staking` pays out rewards by.
+#[cfg(feature = "npos")]
+impl pallet_authorship::Trait for Runtime {
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Consensus>;
+	type UncleGenerations = UncleGenerations;
+	type FilterUncle = ();
+	type EventHandler = Staking;
+}
+
+// Yearly inflation paid out as staking rewards, from 2.5% with nothing staked up to 10% at the
+// ideal half of the issuance staked, then falling off again.
+#[cfg(feature = "npos")]
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: sp_runtime::curve::PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+#[cfg(feature = "npos")]
+parameter_types! {
+	// an era is 6 sessions, i.e. 6 hours
+	pub const SessionsPerEra: sp_staking::SessionIndex = 6;
+	pub const BondingDuration: pallet_staking::EraIndex = 4 * 7;
+	pub const SlashDeferDuration: pallet_staking::EraIndex = 4 * 2;
+	pub const RewardCurve: &'static sp_runtime::curve::PiecewiseLinear<'static> = &REWARD_CURVE;
+	/// Offchain workers submit election solutions in the last quarter of an era's final session.
+	pub ElectionLookahead: BlockNumber = SessionPeriod::get() / 4;
+	pub const MaxNominatorRewardedPerValidator: u32 = 64;
+	pub const MaxIterations: u32 = 10;
+	pub MinSolutionScoreBump: Perbill = Perbill::from_rational_approximation(5u32, 10_000);
+	pub OffchainSolutionWeightLimit: Weight = MaximumExtrinsicWeight::get()
+		.saturating_sub(BlockExecutionWeight::get())
+		.saturating_sub(ExtrinsicBaseWeight::get());
+	pub const StakingUnsignedPriority: sp_runtime::transaction_validity::TransactionPriority =
+		sp_runtime::transaction_validity::TransactionPriority::max_value() / 2;
+}
+
+/// Converts balances to election vote weights, scaled so that the total issuance fits a `u64`.
+#[cfg(feature = "npos")]
+pub struct CurrencyToVoteHandler;
+
+#[cfg(feature = "npos")]
+impl CurrencyToVoteHandler {
+	fn factor() -> Balance {
+		(Balances::total_issuance() / u64::max_value() as Balance).max(1)
+	}
+}
+
+#[cfg(feature = "npos")]
+impl sp_runtime::traits::Convert<Balance, u64> for CurrencyToVoteHandler {
+	fn convert(x: Balance) -> u64 {
+		(x / Self::factor()) as u64
+	}
+}
+
+#[cfg(feature = "npos")]
+impl sp_runtime::traits::Convert<u128, Balance> for CurrencyToVoteHandler {
+	fn convert(x: u128) -> Balance {
+		x * Self::factor()
+	}
+}
+
+/// Validators are elected by stake each era. Rewards are minted along `REWARD_CURVE`, and
+/// offences reported through `Offences`, such as GRANDPA equivocations, are slashed.
+#[cfg(feature = "npos")]
+impl pallet_staking::Trait for Runtime {
+	type Currency = Balances;
+	type UnixTime = Timestamp;
+	type CurrencyToVote = CurrencyToVoteHandler;
+	/// Inflation beyond what the curve pays to stakers goes to the treasury.
+	type RewardRemainder = Treasury;
+	type Event = Event;
+	type Slash = Treasury;
+	type Reward = ();
+	type SessionsPerEra = SessionsPerEra;
+	type BondingDuration = BondingDuration;
+	type SlashDeferDuration = SlashDeferDuration;
+	/// Slashes are deferred for `SlashDeferDuration` eras, in which the council may cancel them.
+	type SlashCancelOrigin = EnsureRootOrHalfCouncil;
+	type SessionInterface = Self;
+	type RewardCurve = RewardCurve;
+	type NextNewSession = Session;
+	type ElectionLookahead = ElectionLookahead;
+	type Call = Call;
+	type MaxIterations = MaxIterations;
+	type MinSolutionScoreBump = MinSolutionScoreBump;
+	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
+	type UnsignedPriority = StakingUnsignedPriority;
+	type OffchainSolutionWeightLimit = OffchainSolutionWeightLimit;
+	type WeightInfo = ();
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime where
+	Call: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = Call;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::CreateSignedTransaction<C> for Runtime where
+	Call: From<C>,
+{
+	fn create_transaction<A: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Index,
+	) -> Option<(Call, <UncheckedExtrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload)> {
+		use sp_runtime::SaturatedConversion;
+
+		// the longest mortality the block hash count allows
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|c| c / 2)
+			.unwrap_or(2) as u64;
+		// offchain workers run on top of block `n` with the block number set to `n + 1`
+		let current_block = System::block_number()
+			.saturated_into::<u64>()
+			.saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra)
+			.map_err(|e| frame_support::debug::warn!("Unable to create signed payload: {:?}", e))
+			.ok()?;
+		let signature = raw_payload.using_encoded(|payload| A::sign(payload, public))?;
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (Indices::unlookup(account), signature, extra)))
+	}
+}
+
 parameter_types! {
-	pub const MinimumPeriod: u64 = SLOT_DURATION / 2;
+	pub const DefaultSlotDuration: u64 = SLOT_DURATION;
+}
+
+impl pallet_block_time::Trait for Runtime {
+	type DefaultSlotDuration = DefaultSlotDuration;
 }
 
 impl pallet_timestamp::Trait for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
-	type OnTimestampSet = Aura;
-	type MinimumPeriod = MinimumPeriod;
+	type OnTimestampSet = Consensus;
+	/// Half the slot duration set at genesis, the Aura or BABE slot duration is twice this.
+	type MinimumPeriod = pallet_block_time::MinimumPeriod<Runtime>;
 	type WeightInfo = ();
 }
 
 parameter_types! {
-	pub const ExistentialDeposit: u128 = 500;
+	pub const IndexDeposit: Balance = 1 * DOLLARS;
+}
+
+impl pallet_indices::Trait for Runtime {
+	type AccountIndex = AccountIndex;
+	type Currency = Balances;
+	type Deposit = IndexDeposit;
+	type Event = Event;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 10 * CENTS;
 	pub const MaxLocks: u32 = 50;
 }
 
@@ -241,73 +570,645 @@ impl pallet_balances::Trait for Runtime {
 	type Balance = Balance;
 	/// The ubiquitous event type.
 	type Event = Event;
-	type DustRemoval = ();
+	type DustRemoval = Treasury;
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 	type WeightInfo = ();
 }
 
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Validator that authored the current block, the stash with `npos`.
+fn block_author() -> Option<AccountId> {
+	let digest = System::digest();
+	let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+
+	pallet_session::FindAccountFromAuthorIndex::<Runtime, Consensus>::find_author(pre_runtime_digests)
+}
+
+/// Pays the block author, or the treasury when the author can't be found.
+pub struct Author;
+impl OnUnbalanced<NegativeImbalance> for Author {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+		match block_author() {
+			Some(author) => Balances::resolve_creating(&author, amount),
+			None => Treasury::on_unbalanced(amount),
+		}
+	}
+}
+
+/// Splits transaction fees 80/20 between the treasury and the block author.
+/// Tips go to the author in full.
+pub struct DealWithFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item=NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			let mut split = fees.ration(80, 20);
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut split.1);
+			}
+			Treasury::on_unbalanced(split.0);
+			Author::on_unbalanced(split.1);
+		}
+	}
+}
+
+/// Maps weight to fee so that an extrinsic of `ExtrinsicBaseWeight` costs a tenth of a cent,
+/// growing linearly from there.
+pub struct WeightToFee;
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		let p = CENTS / 10;
+		let q = Balance::from(ExtrinsicBaseWeight::get());
+		smallvec![WeightToFeeCoefficient {
+			degree: 1,
+			negative: false,
+			coeff_frac: Perbill::from_rational_approximation(p % q, q),
+			coeff_integer: p / q,
+		}]
+	}
+}
+
 parameter_types! {
-	pub const TransactionByteFee: Balance = 1;
+	pub const TransactionByteFee: Balance = 10 * MILLICENTS;
+	/// Fees go up while blocks are fuller than this and down while they are emptier.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(3, 100_000);
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000u128);
 }
 
 impl pallet_transaction_payment::Trait for Runtime {
 	type Currency = Balances;
-	type OnTransactionPayment = ();
+	type OnTransactionPayment = DealWithFees;
 	type TransactionByteFee = TransactionByteFee;
-	type WeightToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ();
+	type WeightToFee = WeightToFee;
+	type FeeMultiplierUpdate =
+		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
+// `no-sudo` builds keep the pallet in its slot, so the indices of the pallets after it stay
+// the same, but without a key or any callable `sudo` call.
 impl pallet_sudo::Trait for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+/// Rejects paused calls, and every `Sudo` call in `no-sudo` builds. Root calls bypass it.
+pub struct BaseFilter;
+impl Filter<Call> for BaseFilter {
+	fn filter(c: &Call) -> bool {
+		if cfg!(feature = "no-sudo") && matches!(c, Call::Sudo(..)) {
+			return false;
+		}
+		pallet_tx_pause::PausedCallFilter::<Runtime>::filter(c)
+	}
+}
+
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Trait<CouncilCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type WeightInfo = ();
+}
+
+/// Root, or more than half of the council. Used for the kitty and PoE admin calls,
+/// so they keep working once the sudo key is retired.
+type EnsureRootOrHalfCouncil = EnsureOneOf<
+	AccountId,
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+>;
+
+/// Root, or three fifths of the council. Approves treasury spending.
+type EnsureRootOrThreeFifthsCouncil = EnsureOneOf<
+	AccountId,
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<_3, _5, AccountId, CouncilCollective>,
+>;
+
+/// Council members may tip from the treasury.
+pub struct CouncilTippers;
+impl Contains<AccountId> for CouncilTippers {
+	fn sorted_members() -> Vec<AccountId> {
+		// genesis members are stored as given, not sorted
+		let mut members = Council::members();
+		members.sort();
+		members
+	}
+}
+impl ContainsLengthBound for CouncilTippers {
+	fn min_len() -> usize {
+		0
+	}
+	fn max_len() -> usize {
+		CouncilMaxMembers::get() as usize
+	}
+}
+
+parameter_types! {
+	pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 1 * DOLLARS;
+	pub const SpendPeriod: BlockNumber = 1 * DAYS;
+	pub const Burn: Permill = Permill::from_percent(0);
+	pub const TipCountdown: BlockNumber = 1 * DAYS;
+	pub const TipFindersFee: Percent = Percent::from_percent(20);
+	pub const TipReportDepositBase: Balance = 1 * DOLLARS;
+	pub const DataDepositPerByte: Balance = 1 * CENTS;
+	pub const BountyDepositBase: Balance = 1 * DOLLARS;
+	pub const BountyDepositPayoutDelay: BlockNumber = 1 * DAYS;
+	pub const BountyUpdatePeriod: BlockNumber = 14 * DAYS;
+	pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
+	pub const BountyValueMinimum: Balance = 5 * DOLLARS;
+	pub const MaximumReasonLength: u32 = 16384;
+}
+
+impl pallet_treasury::Trait for Runtime {
+	type ModuleId = TreasuryModuleId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureRootOrThreeFifthsCouncil;
+	type RejectOrigin = EnsureRootOrHalfCouncil;
+	type Tippers = CouncilTippers;
+	type TipCountdown = TipCountdown;
+	type TipFindersFee = TipFindersFee;
+	type TipReportDepositBase = TipReportDepositBase;
+	type DataDepositPerByte = DataDepositPerByte;
+	type Event = Event;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BountyDepositBase = BountyDepositBase;
+	type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+	type BountyUpdatePeriod = BountyUpdatePeriod;
+	type BountyCuratorDeposit = BountyCuratorDeposit;
+	type BountyValueMinimum = BountyValueMinimum;
+	type MaximumReasonLength = MaximumReasonLength;
+	type BurnDestination = ();
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Trait for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+	pub const VotingPeriod: BlockNumber = 7 * DAYS;
+	pub const FastTrackVotingPeriod: BlockNumber = 3 * HOURS;
+	pub const InstantAllowed: bool = true;
+	pub const MinimumDeposit: Balance = 100 * DOLLARS;
+	pub const EnactmentPeriod: BlockNumber = 1 * DAYS;
+	pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+	pub const PreimageByteDeposit: Balance = 1 * CENTS;
+	pub const MaxVotes: u32 = 100;
+}
+
+// Passed referenda are enacted with the root origin, this is how `set_code` and
+// other root calls get through once sudo is gone.
+impl pallet_democracy::Trait for Runtime {
+	type Proposal = Call;
+	type Event = Event;
+	type Currency = Balances;
+	type EnactmentPeriod = EnactmentPeriod;
+	type LaunchPeriod = LaunchPeriod;
+	type VotingPeriod = VotingPeriod;
+	type MinimumDeposit = MinimumDeposit;
+	/// A straight majority of the council can decide what their next motion is.
+	type ExternalOrigin = pallet_collective::EnsureProportionAtLeast<_1, _2, AccountId, CouncilCollective>;
+	/// A super-majority can have the next scheduled referendum be a straight majority-carries vote.
+	type ExternalMajorityOrigin = pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	/// A unanimous council can have the next scheduled referendum be a straight default-carries
+	/// (NTB) vote.
+	type ExternalDefaultOrigin = pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+	/// Three quarters of the council can have an external referendum tabled immediately with a
+	/// shorter voting period.
+	type FastTrackOrigin = pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	type InstantOrigin = pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+	type InstantAllowed = InstantAllowed;
+	type FastTrackVotingPeriod = FastTrackVotingPeriod;
+	/// Three quarters of the council can cancel a referendum that already passed.
+	type CancellationOrigin = pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+	/// Any single council member may veto a coming external proposal, once per cool-off period.
+	type VetoOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type CooloffPeriod = CooloffPeriod;
+	type PreimageByteDeposit = PreimageByteDeposit;
+	type OperationalPreimageOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type Slash = Treasury;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type MaxVotes = MaxVotes;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const MaxClaimLength: u32 = 255;
+	pub const ClaimDeposit: Balance = 1 * DOLLARS;
+	pub const MaxExpiringPerBlock: u32 = 100;
+	pub const MaxUriLength: u32 = 255;
+	pub const UriByteDeposit: Balance = 1 * CENTS;
+	pub const AttestationPeriod: BlockNumber = DAYS;
+	pub const PoeUnsignedPriority: sp_runtime::transaction_validity::TransactionPriority =
+		sp_runtime::transaction_validity::TransactionPriority::max_value() / 4;
 }
 
 impl pallet_poe::Trait for Runtime {
 	type Event = Event;
 	type MaxClaimLength = MaxClaimLength;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type Currency = Balances;
+	type ClaimDeposit = ClaimDeposit;
+	type Forfeited = Treasury;
+	type MaxExpiringPerBlock = MaxExpiringPerBlock;
+	type AttesterId = pallet_poe::crypto::AttesterId;
+	type MaxUriLength = MaxUriLength;
+	type UriByteDeposit = UriByteDeposit;
+	type AttestationPeriod = AttestationPeriod;
+	type UnsignedPriority = PoeUnsignedPriority;
 }
 
 parameter_types! {
-    pub const NewKittyReserve: u32 = 1000000;
+	pub const NewKittyReserve: Balance = 1 * DOLLARS;
+	pub const MaxBatchSize: u32 = 32;
+	pub const ReuseKittyIds: bool = true;
+	pub const MarketCommission: Permill = Permill::from_percent(2);
 }
 
 impl pallet_kitties::Trait for Runtime {
 	type Event = Event;
 	type Randomness = RandomnessCollectiveFlip;
-	type KittyIndex = KittyInex;
+	type KittyIndex = KittyIndex;
 	type NewKittyReserve = NewKittyReserve;
     type Currency = pallet_balances::Module<Self>;
+	type MaxBatchSize = MaxBatchSize;
+	type OnKittyTransfer = ();
+	type OnKittyBurn = KittyArena;
+	type ReuseKittyIds = ReuseKittyIds;
+	type MintOrigin = EnsureRootOrHalfCouncil;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type MarketCommission = MarketCommission;
+	type OnCommission = Treasury;
+}
+
+parameter_types! {
+	pub const MinimumWager: Balance = 10 * CENTS;
+	pub const MaxLeaderboardSize: u32 = 100;
+	pub const FightDelay: BlockNumber = 3;
+}
+
+impl pallet_kitty_arena::Trait for Runtime {
+	type Event = Event;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type KittyIndex = KittyIndex;
+	type Kitties = KittiesModule;
+	type MinimumWager = MinimumWager;
+	type MaxLeaderboardSize = MaxLeaderboardSize;
+	type FightDelay = FightDelay;
+}
+
+/// Picks the `(kitty_id, price)` of market sales out of the runtime's events.
+pub struct KittySales;
+
+impl sp_runtime::traits::Convert<Event, Option<(KittyIndex, Balance)>> for KittySales {
+	fn convert(event: Event) -> Option<(KittyIndex, Balance)> {
+		match event {
+			Event::pallet_kitties(pallet_kitties::RawEvent::Sold(_, _, kitty_id, price)) => Some((kitty_id, price)),
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const SaleWindow: BlockNumber = 7 * DAYS;
+	pub const ReportInterval: BlockNumber = HOURS;
+}
+
+impl pallet_kitty_oracle::Trait for Runtime {
+	type Event = Event;
+	type AuthorityId = pallet_kitty_oracle::crypto::AuthorityId;
+	type Currency = Balances;
+	type KittyIndex = KittyIndex;
+	type Kitties = KittiesModule;
+	type KittySales = KittySales;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type SaleWindow = SaleWindow;
+	type ReportInterval = ReportInterval;
+}
+
+parameter_types! {
+	// One storage item; key size is 32 bytes; value is a u16 count plus the proxy list.
+	pub const ProxyDepositBase: Balance = deposit(1, 8);
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = deposit(0, 33);
+	pub const MaxProxies: u16 = 32;
+	pub const AnnouncementDepositBase: Balance = deposit(1, 8);
+	pub const AnnouncementDepositFactor: Balance = deposit(0, 66);
+	pub const MaxPending: u16 = 32;
+}
+
+/// The kinds of calls a proxy account may make on behalf of its delegator.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Calls that neither move balances nor hand kitties or claims to someone else.
+	NonTransfer,
+	/// Kitties pallet calls only, for managing a kitty inventory.
+	Kitties,
+	/// PoE pallet calls only, for managing claims.
+	Poe,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// an allow-list, so calls of pallets added later are denied until reviewed
+			ProxyType::NonTransfer => matches!(
+				c,
+				Call::System(..) |
+				Call::Timestamp(..) |
+				Call::Session(..) |
+				Call::Indices(pallet_indices::Call::claim(..)) |
+				Call::Indices(pallet_indices::Call::free(..)) |
+				Call::Indices(pallet_indices::Call::freeze(..)) |
+				Call::Identity(..) |
+				Call::Council(..) |
+				Call::Democracy(..) |
+				Call::Proxy(..) |
+				Call::Multisig(..) |
+				Call::Vesting(pallet_vesting::Call::vest(..)) |
+				Call::Vesting(pallet_vesting::Call::vest_other(..)) |
+				Call::PoeModule(pallet_poe::Call::create_claim(..)) |
+				Call::PoeModule(pallet_poe::Call::revoke_claim(..)) |
+				Call::PoeModule(pallet_poe::Call::set_claim_expiry(..)) |
+				Call::PoeModule(pallet_poe::Call::set_content_uri(..)) |
+				Call::KittiesModule(pallet_kitties::Call::create(..)) |
+				Call::KittiesModule(pallet_kitties::Call::breed(..)) |
+				Call::KittiesModule(pallet_kitties::Call::cancel_swap(..)) |
+				Call::KittyArena(pallet_kitty_arena::Call::cancel(..))
+			),
+			ProxyType::Kitties => matches!(c, Call::KittiesModule(..)),
+			ProxyType::Poe => matches!(c, Call::PoeModule(..)),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			// `Kitties` and `Poe` may transfer, which `NonTransfer` may not
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
+	type WeightInfo = ();
+	type MaxPending = MaxPending;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	// One storage item; key size is 32; value is size 4+4+16+32 bytes = 56 bytes.
+	pub const MultisigDepositBase: Balance = deposit(1, 88);
+	// Additional storage item size of 32 bytes.
+	pub const MultisigDepositFactor: Balance = deposit(0, 32);
+	pub const MaxSignatories: u16 = 100;
+}
+
+impl pallet_multisig::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	// Covers roughly 1000 bytes of identity info at 1 CENT per byte.
+	pub const BasicDeposit: Balance = 10 * DOLLARS;
+	pub const FieldDeposit: Balance = 250 * CENTS;
+	pub const SubAccountDeposit: Balance = 2 * DOLLARS;
+	pub const MaxSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+	pub const MaxRegistrars: u32 = 20;
+}
+
+impl pallet_identity::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaxSubAccounts = MaxSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type MaxRegistrars = MaxRegistrars;
+	type Slashed = Treasury;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type RegistrarOrigin = EnsureRootOrHalfCouncil;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 1 * DOLLARS;
+}
+
+impl pallet_vesting::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	/// Block production, finality, validator rotation and governance keep working during a pause.
+	pub const UnpausablePallets: &'static [&'static [u8]] = &[
+		b"System", b"Timestamp", b"Aura", b"Babe", b"Grandpa", b"Session",
+		b"Council", b"Democracy", b"Scheduler", b"TxPause",
+	];
+}
+
+impl pallet_tx_pause::Trait for Runtime {
+	type Event = Event;
+	type PauseOrigin = EnsureRootOrHalfCouncil;
+	type UnpausablePallets = UnpausablePallets;
+}
+
+/// Calls accounts may schedule for themselves: kitty and claim management only.
+pub struct ScheduledCallFilter;
+impl Filter<Call> for ScheduledCallFilter {
+	fn filter(c: &Call) -> bool {
+		matches!(c, Call::KittiesModule(..) | Call::PoeModule(..))
+	}
+}
+
+parameter_types! {
+	pub const MaxScheduledPerAccount: u32 = 10;
+	pub const MaxScheduleDelay: BlockNumber = 30 * DAYS;
+	// the call is stored until it runs, like a small preimage
+	pub const ScheduleDeposit: Balance = 1 * DOLLARS;
+}
+
+impl pallet_scheduled_calls::Trait for Runtime {
+	type Event = Event;
+	type ScheduledCall = Call;
+	type CallFilter = ScheduledCallFilter;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MaxScheduledPerAccount = MaxScheduledPerAccount;
+	type MaxScheduleDelay = MaxScheduleDelay;
+	type Currency = Balances;
+	type ScheduleDeposit = ScheduleDeposit;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
-construct_runtime!(
-	pub enum Runtime where
-		Block = Block,
-		NodeBlock = opaque::Block,
-		UncheckedExtrinsic = UncheckedExtrinsic
-	{
-		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
-		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
-		Aura: pallet_aura::{Module, Config<T>, Inherent},
-		Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event},
-		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
-		TransactionPayment: pallet_transaction_payment::{Module, Storage},
-		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
-		// Include the custom logic from the template pallet in the runtime.
-		PoeModule: pallet_poe::{Module, Call, Storage, Event<T>},
-		KittiesModule: pallet_kitties::{Module, Call, Storage, Event<T>},
+//
+// The pallet assigning slots differs with `consensus-babe`, the pallets managing validators
+// with `npos`, and `no-sudo` leaves `Sudo` without a genesis config.
+macro_rules! construct_node_runtime {
+	({ $($consensus_pallet:tt)* } { $($sudo_pallet:tt)* } { $($validator_pallets:tt)* }) => {
+		construct_runtime!(
+			pub enum Runtime where
+				Block = Block,
+				NodeBlock = opaque::Block,
+				UncheckedExtrinsic = UncheckedExtrinsic
+			{
+				System: frame_system::{Module, Call, Config, Storage, Event<T>},
+				RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+				Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
+				$($consensus_pallet)*
+				Grandpa: pallet_grandpa::{Module, Call, Storage, Config, Event, ValidateUnsigned},
+				Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+				TransactionPayment: pallet_transaction_payment::{Module, Storage},
+				$($sudo_pallet)*
+				// Include the custom logic from the template pallet in the runtime.
+				PoeModule: pallet_poe::{Module, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
+				KittiesModule: pallet_kitties::{Module, Call, Storage, Config, Event<T>},
+				KittyArena: pallet_kitty_arena::{Module, Call, Storage, Event<T>},
+				// Appended so the call indices of the pallets above stay the same.
+				Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+				Democracy: pallet_democracy::{Module, Call, Storage, Config, Event<T>},
+				Council: pallet_collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
+				Treasury: pallet_treasury::{Module, Call, Storage, Config, Event<T>},
+				Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
+				Multisig: pallet_multisig::{Module, Call, Storage, Event<T>},
+				TxPause: pallet_tx_pause::{Module, Call, Storage, Event},
+				Indices: pallet_indices::{Module, Call, Storage, Config<T>, Event<T>},
+				Identity: pallet_identity::{Module, Call, Storage, Event<T>},
+				Vesting: pallet_vesting::{Module, Call, Storage, Event<T>, Config<T>},
+				ScheduledCalls: pallet_scheduled_calls::{Module, Call, Storage, Event<T>},
+				BlockTime: pallet_block_time::{Module, Storage, Config},
+				$($validator_pallets)*
+				KittyOracle: pallet_kitty_oracle::{Module, Call, Storage, Config<T>, Event<T>},
+			}
+		);
 	}
-);
+}
 
-/// The address format for describing accounts.
-pub type Address = AccountId;
+#[cfg(not(feature = "no-sudo"))]
+macro_rules! construct_node_runtime_with_sudo {
+	({ $($consensus_pallet:tt)* } { $($validator_pallets:tt)* }) => {
+		construct_node_runtime!({ $($consensus_pallet)* } {
+			Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
+		} { $($validator_pallets)* });
+	}
+}
+
+// Leaving `Sudo` out would move every pallet after it up one index and break the calls already
+// encoded in scheduler agendas, preimages and proposals.
+#[cfg(feature = "no-sudo")]
+macro_rules! construct_node_runtime_with_sudo {
+	({ $($consensus_pallet:tt)* } { $($validator_pallets:tt)* }) => {
+		construct_node_runtime!({ $($consensus_pallet)* } {
+			Sudo: pallet_sudo::{Module, Call, Storage, Event<T>},
+		} { $($validator_pallets)* });
+	}
+}
+
+#[cfg(not(feature = "npos"))]
+macro_rules! construct_node_runtime_with_validators {
+	($($consensus_pallet:tt)*) => {
+		construct_node_runtime_with_sudo!({ $($consensus_pallet)* } {
+			// `ValidatorSet` builds before `Session`, whose genesis reads the set
+			ValidatorSet: pallet_validator_set::{Module, Call, Storage, Event<T>, Config<T>},
+			Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
+			Historical: pallet_session_historical::{Module},
+			Offences: pallet_offences::{Module, Call, Storage, Event},
+		});
+	}
+}
+
+#[cfg(feature = "npos")]
+macro_rules! construct_node_runtime_with_validators {
+	($($consensus_pallet:tt)*) => {
+		construct_node_runtime_with_sudo!({ $($consensus_pallet)* } {
+			Authorship: pallet_authorship::{Module, Call, Storage, Inherent},
+			// `Staking` builds before `Session`, whose genesis asks it for the first validators
+			Staking: pallet_staking::{Module, Call, Config<T>, Storage, Event<T>, ValidateUnsigned},
+			Session: pallet_session::{Module, Call, Storage, Event, Config<T>},
+			Historical: pallet_session_historical::{Module},
+			Offences: pallet_offences::{Module, Call, Storage, Event},
+		});
+	}
+}
+
+#[cfg(not(feature = "consensus-babe"))]
+construct_node_runtime_with_validators! {
+	Aura: pallet_aura::{Module, Config<T>, Inherent},
+}
+
+#[cfg(feature = "consensus-babe")]
+construct_node_runtime_with_validators! {
+	Babe: pallet_babe::{Module, Call, Storage, Config, Inherent, ValidateUnsigned},
+}
+
+/// The address format for describing accounts, either an account ID or an account index.
+pub type Address = <Indices as StaticLookup>::Source;
 /// Block header type as expected by this runtime.
 pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 /// Block type as expected by this runtime.
@@ -328,6 +1229,8 @@ pub type SignedExtra = (
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
@@ -337,8 +1240,112 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllModules,
+	Migrations,
 >;
 
+/// Storage migrations run on runtime upgrades, before the pallets' own.
+#[cfg(not(any(feature = "npos", feature = "consensus-babe")))]
+pub type Migrations = (WidenKittyIndex, InitSessionKeys, RetireSudo);
+/// Storage migrations run on runtime upgrades, before the pallets' own.
+///
+/// NPoS and BABE chains start with sessions, so there are no authorities to hand over.
+#[cfg(any(feature = "npos", feature = "consensus-babe"))]
+pub type Migrations = (WidenKittyIndex, RetireSudo);
+
+/// Re-encodes kitty storage written while `KittyIndex` was `u32`.
+pub struct WidenKittyIndex;
+
+impl frame_support::traits::OnRuntimeUpgrade for WidenKittyIndex {
+	fn on_runtime_upgrade() -> Weight {
+		// `KittiesCount` is the first kitty item ever written, its width tells the layout
+		if pallet_kitties::migrations::stored_index_width::<Runtime>() != Some(sp_std::mem::size_of::<u32>()) {
+			return RocksDbWeight::get().reads(1);
+		}
+
+		pallet_kitties::migrations::widen_kitty_index::<Runtime, u32>()
+			.saturating_add(pallet_kitty_arena::migrations::widen_kitty_index::<Runtime, u32>())
+	}
+}
+
+/// Removes the storage `pallet_sudo` left behind, once a runtime built with `no-sudo` is enacted.
+pub struct RetireSudo;
+
+impl frame_support::traits::OnRuntimeUpgrade for RetireSudo {
+	fn on_runtime_upgrade() -> Weight {
+		if !cfg!(feature = "no-sudo") {
+			return 0;
+		}
+
+		// the key is the only item, clearing it again on later upgrades is a no-op
+		frame_support::storage::migration::take_storage_value::<AccountId>(b"Sudo", b"Key", &[]);
+		RocksDbWeight::get().reads_writes(1, 1)
+	}
+}
+
+/// Hands the authority set over to `pallet_session` on chains started before it was added.
+///
+/// Every Aura authority becomes a validator, under the account of its Aura key, with its
+/// GRANDPA key of the same position. There is no historical root for the session the upgrade
+/// happens in, so key ownership proofs work from the next session on.
+#[cfg(not(any(feature = "npos", feature = "consensus-babe")))]
+pub struct InitSessionKeys;
+
+#[cfg(not(any(feature = "npos", feature = "consensus-babe")))]
+impl frame_support::traits::OnRuntimeUpgrade for InitSessionKeys {
+	fn on_runtime_upgrade() -> Weight {
+		use frame_support::{storage::migration::put_storage_value, StorageHasher, Twox64Concat};
+
+		if !Session::validators().is_empty() {
+			return RocksDbWeight::get().reads(1);
+		}
+
+		let keys: Vec<(AccountId, opaque::SessionKeys)> = Aura::authorities().into_iter()
+			.zip(Grandpa::grandpa_authorities())
+			.filter_map(|(aura, (grandpa, _))| {
+				let mut raw: &[u8] = aura.as_ref();
+				let validator = AccountId::decode(&mut raw).ok()?;
+				Some((validator, opaque::SessionKeys { aura, grandpa }))
+			})
+			.collect();
+		let validators: Vec<AccountId> = keys.iter().map(|(validator, _)| validator.clone()).collect();
+
+		for (validator, session_keys) in keys.iter() {
+			put_storage_value(b"Session", b"NextKeys", &Twox64Concat::hash(&validator.encode()), session_keys);
+			for key_type in <opaque::SessionKeys as OpaqueKeys>::key_ids() {
+				let owner_key = (*key_type, session_keys.get_raw(*key_type).to_vec());
+				put_storage_value(b"Session", b"KeyOwner", &Twox64Concat::hash(&owner_key.encode()), validator);
+			}
+		}
+		put_storage_value(b"Session", b"Validators", &[], &validators);
+		put_storage_value(b"Session", b"QueuedKeys", &[], &keys);
+		pallet_validator_set::Validators::<Runtime>::put(&validators);
+
+		let writes = 3 + keys.len() as Weight * (1 + <opaque::SessionKeys as OpaqueKeys>::key_ids().len() as Weight);
+		RocksDbWeight::get().reads_writes(3, writes)
+	}
+}
+
+/// Run every storage migration of a runtime upgrade between the pallets' pre- and post-upgrade checks.
+///
+/// Meant to be called natively on a snapshot of exported state, see the node's `try-runtime` command.
+#[cfg(feature = "std")]
+pub fn try_runtime_upgrade() -> Result<Weight, &'static str> {
+	use frame_support::traits::OnRuntimeUpgrade;
+
+	pallet_poe::migrations::pre_migrate::<Runtime>()?;
+	pallet_kitties::migrations::pre_migrate::<Runtime>()?;
+
+	// same order as `Executive::initialize_block` runs them: System, the custom migrations, then the pallets
+	let weight = <System as OnRuntimeUpgrade>::on_runtime_upgrade()
+		.saturating_add(<Migrations as OnRuntimeUpgrade>::on_runtime_upgrade())
+		.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+
+	pallet_poe::migrations::post_migrate::<Runtime>()?;
+	pallet_kitties::migrations::post_migrate::<Runtime>()?;
+
+	Ok(weight)
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -400,6 +1407,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(not(feature = "consensus-babe"))]
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> u64 {
 			Aura::slot_duration()
@@ -410,6 +1418,45 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(feature = "consensus-babe")]
+	impl sp_consensus_babe::BabeApi<Block> for Runtime {
+		fn configuration() -> sp_consensus_babe::BabeGenesisConfiguration {
+			sp_consensus_babe::BabeGenesisConfiguration {
+				slot_duration: Babe::slot_duration(),
+				epoch_length: EpochDuration::get(),
+				c: PRIMARY_PROBABILITY,
+				genesis_authorities: Babe::authorities(),
+				randomness: Babe::randomness(),
+				allowed_slots: sp_consensus_babe::AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			}
+		}
+
+		fn current_epoch_start() -> sp_consensus_babe::SlotNumber {
+			Babe::current_epoch_start()
+		}
+
+		fn generate_key_ownership_proof(
+			_slot_number: sp_consensus_babe::SlotNumber,
+			authority_id: sp_consensus_babe::AuthorityId,
+		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
+			Historical::prove((sp_consensus_babe::KEY_TYPE, authority_id))
+				.map(|proof| proof.encode())
+				.map(sp_consensus_babe::OpaqueKeyOwnershipProof::new)
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: sp_consensus_babe::EquivocationProof<<Block as BlockT>::Header>,
+			key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Babe::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			opaque::SessionKeys::generate(seed)
@@ -428,23 +1475,27 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|proof| proof.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -463,6 +1514,18 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitty_oracle_runtime_api::KittyOracleApi<Block, Balance, BlockNumber> for Runtime {
+		fn floor_price(
+			tier: pallet_kitty_oracle::RarityTier,
+		) -> Option<pallet_kitty_oracle::FloorPrice<Balance, BlockNumber>> {
+			KittyOracle::floor_price(tier)
+		}
+
+		fn floor_prices() -> Vec<(pallet_kitty_oracle::RarityTier, pallet_kitty_oracle::FloorPrice<Balance, BlockNumber>)> {
+			KittyOracle::floor_prices()
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -0,0 +1,82 @@
+use crate::*;
+use frame_support::weights::{DispatchClass, DispatchInfo, Pays};
+
+/// Fee of an extrinsic of `len` bytes and no weight beyond `ExtrinsicBaseWeight`, without tip.
+fn base_fee(len: u32) -> Balance {
+	let info = DispatchInfo { weight: 0, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+	sp_io::TestExternalities::new_empty().execute_with(|| TransactionPayment::compute_fee(len, &info, 0))
+}
+
+#[test]
+fn base_extrinsic_fee_is_pinned() {
+	// a tenth of a cent for the weight, ten millicents per byte
+	assert_eq!(base_fee(0), CENTS / 10);
+	assert_eq!(base_fee(100), CENTS / 10 + CENTS);
+}
+
+#[test]
+fn deposits_exceed_base_extrinsic_fee() {
+	// more than a transfer, which is well below 200 bytes
+	let fee = base_fee(200);
+
+	assert!(ExistentialDeposit::get() > fee);
+	assert!(NewKittyReserve::get() > fee);
+	assert!(ClaimDeposit::get() > fee);
+	assert!(MinimumWager::get() > fee);
+	assert!(MinimumDeposit::get() > fee);
+	assert!(ProposalBondMinimum::get() > fee);
+	assert!(IndexDeposit::get() > fee);
+	assert!(ScheduleDeposit::get() > fee);
+}
+
+#[test]
+fn non_transfer_proxy_allows_listed_calls_only() {
+	let allowed = |call: Call| ProxyType::NonTransfer.filter(&call);
+	let who = Address::from(AccountId::default());
+
+	assert!(allowed(Call::System(frame_system::Call::remark(vec![]))));
+	assert!(allowed(Call::PoeModule(pallet_poe::Call::create_claim(vec![0]))));
+	assert!(allowed(Call::KittiesModule(pallet_kitties::Call::create())));
+
+	assert!(!allowed(Call::Balances(pallet_balances::Call::transfer(who.clone(), 1))));
+	assert!(!allowed(Call::Indices(pallet_indices::Call::transfer(AccountId::default(), 0))));
+	assert!(!allowed(Call::PoeModule(pallet_poe::Call::transfer_claim(vec![0], who.clone()))));
+	assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::buy(0, 1))));
+	assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::accept_swap(0))));
+	assert!(!allowed(Call::KittiesModule(pallet_kitties::Call::ask(0, Some(1)))));
+	assert!(!allowed(Call::Treasury(pallet_treasury::Call::propose_spend(1, who))));
+	assert!(!allowed(Call::KittyArena(pallet_kitty_arena::Call::accept(0))));
+
+	assert!(!ProxyType::NonTransfer.is_superset(&ProxyType::Kitties));
+	assert!(!ProxyType::NonTransfer.is_superset(&ProxyType::Poe));
+}
+
+#[test]
+fn unpausable_pallets_match_runtime_names() {
+	use frame_support::traits::PalletInfo as _;
+
+	let unpausable = UnpausablePallets::get();
+	let listed = |name: Option<&'static str>| {
+		let name = name.expect("pallet is part of the runtime");
+		unpausable.iter().any(|listed| *listed == name.as_bytes())
+	};
+
+	assert!(listed(PalletInfo::name::<System>()));
+	assert!(listed(PalletInfo::name::<Timestamp>()));
+	assert!(listed(PalletInfo::name::<Grandpa>()));
+	assert!(listed(PalletInfo::name::<Council>()));
+	assert!(listed(PalletInfo::name::<Democracy>()));
+	assert!(listed(PalletInfo::name::<Scheduler>()));
+	assert!(listed(PalletInfo::name::<TxPause>()));
+	assert!(!listed(PalletInfo::name::<KittiesModule>()));
+}
+
+#[test]
+fn base_filter_rejects_sudo_only_without_sudo() {
+	let call = Call::Sudo(pallet_sudo::Call::set_key(Address::from(AccountId::default())));
+
+	sp_io::TestExternalities::new_empty().execute_with(|| {
+		assert_eq!(BaseFilter::filter(&call), !cfg!(feature = "no-sudo"));
+		assert!(BaseFilter::filter(&Call::System(frame_system::Call::remark(vec![]))));
+	});
+}